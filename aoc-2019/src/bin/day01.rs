@@ -13,7 +13,7 @@ fn main() {
                 let mut mass = l.parse().unwrap();
                 let mut tot_fuel = 0;
                 loop {
-                    mass = fuel_cost(mass).max(0);
+                    mass = fuel_cost(mass);
                     if mass == 0 {
                         break;
                     }
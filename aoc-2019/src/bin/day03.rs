@@ -1,6 +1,7 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
-use itertools::{iproduct, Itertools};
+use aoc_helpers::geometry::{Intersection, Line2};
+use itertools::iproduct;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Pos {
@@ -23,42 +24,27 @@ struct Segment {
 
 impl Segment {
     fn intersects(&self, other: &Self) -> Option<Pos> {
-        if (self.start.x == self.end.x && other.start.x == other.end.x)
-            || (self.start.y == self.end.y && other.start.y == other.end.y)
-        {
-            // going the same direction, can't intersect
-            return None;
-        }
-        let (horiz, vert) = if self.start.x == self.end.x {
-            // own x's are the same, so it's vertical
-            (other, self)
-        } else {
-            (self, other)
+        let to_line = |segment: &Self| {
+            Line2::segment(
+                [segment.start.x, segment.start.y].map(i64::from),
+                [segment.end.x, segment.end.y].map(i64::from),
+            )
         };
-        if horiz.start.y < vert.start.y.min(vert.end.y) {
-            // horiz is lower than entire segment, can't intersect
-            return None;
-        }
-        if horiz.start.y > vert.start.y.max(vert.end.y) {
-            // horiz is higher than entire segment, can't intersect
+        let (line, other_line) = (to_line(self), to_line(other));
+        if line.is_parallel(&other_line) {
+            // running along each other, or just touching end to end, doesn't count as crossing
             return None;
         }
-        if vert.start.x < horiz.start.x.min(horiz.end.x) {
-            // vert is to the left of entire segment, can't intersect
+        let Intersection::Point { point, .. } = line.intersect(&other_line) else {
             return None;
-        }
-        if vert.start.x > horiz.start.x.max(horiz.end.x) {
-            // vert is to the right of entire segment, can't intersect
-            return None;
-        }
-        if vert.start.x == 0 && horiz.start.y == 0 {
+        };
+        let [x, y] = point.map(|coord| i32::try_from(coord.to_integer()?).ok());
+        let pos = Pos { x: x?, y: y? };
+        if pos == (Pos { x: 0, y: 0 }) {
             // origin, doesn't count
             return None;
         }
-        Some(Pos {
-            x: vert.start.x,
-            y: horiz.start.y,
-        })
+        Some(pos)
     }
 }
 
@@ -149,4 +135,16 @@ mod tests {
         };
         assert_eq!(vert.intersects(&horiz), None);
     }
+    #[test]
+    fn test_segment_intersects_end_to_end() {
+        let first = Segment {
+            start: Pos { x: 5, y: 5 },
+            end: Pos { x: 5, y: 20 },
+        };
+        let second = Segment {
+            start: Pos { x: 5, y: 20 },
+            end: Pos { x: 5, y: 30 },
+        };
+        assert_eq!(first.intersects(&second), None);
+    }
 }
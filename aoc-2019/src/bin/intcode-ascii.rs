@@ -0,0 +1,53 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery)]
+
+//! `cargo run -p aoc-2019 --bin intcode-ascii -- program.txt [script.txt]`
+//!
+//! Plays an ASCII Intcode program from the terminal. With a script, its commands are replayed
+//! first and then it's over to you.
+
+use std::io::{self, Write};
+
+use aoc_2019::intcode::ascii::{Ascii, Response};
+use aoc_2019::intcode::IntCode;
+
+fn show(response: &Response) {
+    print!("{}", response.text);
+    for value in &response.values {
+        println!("{value}");
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .expect("usage: intcode-ascii <program file> [script file]");
+    let program = std::fs::read_to_string(&path).expect("can't read the program");
+    let intcode: IntCode = program.parse().expect("not an Intcode program");
+    let mut ascii = Ascii::new(intcode);
+
+    let mut halted = if let Some(script) = args.next() {
+        let script = std::fs::read_to_string(script).expect("can't read the script");
+        let transcript = ascii.run_script(&script).expect("program failed");
+        print!("{}", transcript.text);
+        transcript.halted
+    } else {
+        let response = ascii.read().expect("program failed");
+        show(&response);
+        response.halted
+    };
+
+    let mut line = String::new();
+    while !halted {
+        print!("> ");
+        let _ = io::stdout().flush();
+        line.clear();
+        if !matches!(io::stdin().read_line(&mut line), Ok(1..)) {
+            break;
+        }
+        ascii.send(line.trim_end_matches(['\r', '\n']));
+        let response = ascii.read().expect("program failed");
+        show(&response);
+        halted = response.halted;
+    }
+}
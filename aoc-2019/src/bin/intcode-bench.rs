@@ -0,0 +1,69 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery)]
+
+//! `cargo run --release -p aoc-2019 --bin intcode-bench`
+//!
+//! Times the plain interpreter against the cached one on a program that's all tight loops.
+
+use std::time::{Duration, Instant};
+
+use aoc_2019::intcode::assemble::assemble;
+use aoc_2019::intcode::cached::CachedIntCode;
+use aoc_2019::intcode::IntCode;
+
+/// Sums i * j for i, j < n, keeping the counters on a stack through the relative base
+const NESTED_LOOPS: &str = "
+            arb  stack
+            in   [rb+0]             ; n
+            add  0, 0, [rb+1]       ; i
+outer:      add  0, 0, [rb+2]       ; j
+inner:      mul  [rb+1], [rb+2], [product]
+            add  [sum], [product], [sum]
+            add  [rb+2], 1, [rb+2]
+            lt   [rb+2], [rb+0], [more]
+            jnz  [more], inner
+            add  [rb+1], 1, [rb+1]
+            lt   [rb+1], [rb+0], [more]
+            jnz  [more], outer
+            out  [sum]
+            hlt
+product:    data 0
+sum:        data 0
+more:       data 0
+stack:      data 0, 0, 0
+";
+
+fn time<F: FnMut() -> Vec<i64>>(name: &str, runs: u32, mut run: F) -> (Duration, Vec<i64>) {
+    let start = Instant::now();
+    let mut outputs = vec![];
+    for _ in 0..runs {
+        outputs = run();
+    }
+    let per_run = start.elapsed() / runs;
+    println!("{name:<8} {per_run:>12.2?} per run");
+    (per_run, outputs)
+}
+
+fn main() {
+    let n = 1000;
+    let runs = 10;
+    let mut program = IntCode::new(assemble(NESTED_LOOPS).unwrap());
+    program.push_input(n);
+
+    let (plain, expected) = time("plain", runs, || program.clone().run_to_halt().unwrap());
+    let (cached, outputs) = time("cached", runs, || {
+        CachedIntCode::new(program.clone()).run_to_halt().unwrap()
+    });
+    assert_eq!(outputs, expected);
+    println!(
+        "speedup  {:>12.2}x",
+        plain.as_secs_f64() / cached.as_secs_f64()
+    );
+
+    let mut profiled = CachedIntCode::new(program).with_profile();
+    profiled.run_to_halt().unwrap();
+    let profile = profiled.profile().unwrap();
+    println!("\n{} instructions, hottest:", profile.total());
+    for (address, count) in profile.hottest(5) {
+        println!("{address:>6}  {count}");
+    }
+}
@@ -0,0 +1,39 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery)]
+
+//! `cargo run -p aoc-2019 --bin intcode-debugger -- program.txt`, then `h` for the commands
+
+use std::io::{self, Write};
+
+use aoc_2019::intcode::debugger::{Command, Debugger};
+use aoc_2019::intcode::IntCode;
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("usage: intcode-debugger <program file>");
+    let program = std::fs::read_to_string(&path).expect("can't read the program");
+    let intcode: IntCode = program.parse().expect("not an Intcode program");
+    let mut debugger = Debugger::new(intcode);
+    println!("{}", debugger.registers());
+
+    let mut line = String::new();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        line.clear();
+        if !matches!(io::stdin().read_line(&mut line), Ok(1..)) {
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.parse::<Command>() {
+            Ok(Command::Quit) => break,
+            Ok(command) => match debugger.execute(&command) {
+                Ok(output) => println!("{output}"),
+                Err(error) => println!("error: {error}"),
+            },
+            Err(error) => println!("{error}"),
+        }
+    }
+}
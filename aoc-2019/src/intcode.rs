@@ -0,0 +1,494 @@
+//! The Intcode computer that most of 2019 is built on.
+//!
+//! Memory is `i64` and grows as needed, every opcode and parameter mode is supported, and anything
+//! the program does wrong comes back as an [`Error`] instead of a panic.
+
+pub mod ascii;
+pub mod assemble;
+pub mod branch;
+pub mod cached;
+pub mod debugger;
+pub mod disassemble;
+mod instruction;
+mod memory;
+pub mod network;
+pub mod robot;
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display};
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+pub use instruction::{Instruction, Mode, Op};
+pub use memory::Memory;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnknownOpcode {
+        address: usize,
+        value: i64,
+    },
+    /// A mode digit that isn't 0, 1 or 2, or a mode given for a parameter the op doesn't have
+    InvalidMode {
+        address: usize,
+        value: i64,
+    },
+    /// The last parameter is written to, so it can't be immediate
+    ImmediateWrite {
+        address: usize,
+    },
+    /// The instruction at `address` tried to read, write or jump to `target`
+    NegativeAddress {
+        address: usize,
+        target: i64,
+    },
+    /// An input instruction at `address` with nothing queued up, when running to completion
+    NoInput {
+        address: usize,
+    },
+    /// The program halted at `address` when it was expected to output something
+    UnexpectedHalt {
+        address: usize,
+    },
+    /// The instruction at `address` computed a value (or relative address) that doesn't fit in
+    /// an `i64`
+    Overflow {
+        address: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOpcode { address, value } => {
+                write!(f, "unknown opcode {value} at {address}")
+            }
+            Self::InvalidMode { address, value } => {
+                write!(f, "invalid parameter modes in {value} at {address}")
+            }
+            Self::ImmediateWrite { address } => {
+                write!(f, "immediate mode write parameter at {address}")
+            }
+            Self::NegativeAddress { address, target } => {
+                write!(f, "negative address {target} used at {address}")
+            }
+            Self::NoInput { address } => write!(f, "no input for instruction at {address}"),
+            Self::UnexpectedHalt { address } => {
+                write!(f, "halted at {address} before sending an output")
+            }
+            Self::Overflow { address } => write!(f, "arithmetic overflow at {address}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Why [`IntCode::run`] stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunState {
+    /// At an input instruction with nothing queued. Push some input and run again to carry on.
+    NeedInput,
+    Output(i64),
+    Halted,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntCode {
+    pub memory: Memory,
+    instruction_pointer: usize,
+    relative_base: i64,
+    inputs: VecDeque<i64>,
+}
+
+/// A cell an instruction wrote to, and what was in it before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Written {
+    address: usize,
+    old: i64,
+}
+
+/// A saved machine state, see [`IntCode::snapshot`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot(IntCode);
+
+impl IntCode {
+    #[must_use]
+    pub fn new(program: Vec<i64>) -> Self {
+        Self {
+            memory: program.into(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn instruction_pointer(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    #[must_use]
+    pub const fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    /// Queues a value for the next input instruction
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    pub fn push_inputs<I: IntoIterator<Item = i64>>(&mut self, values: I) {
+        self.inputs.extend(values);
+    }
+
+    /// Inputs queued up but not read yet
+    #[must_use]
+    pub fn pending_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// The instruction at the instruction pointer
+    ///
+    /// # Errors
+    /// If it isn't a valid instruction
+    pub fn current_instruction(&self) -> Result<Instruction, Error> {
+        Instruction::decode(
+            self.instruction_pointer,
+            self.memory[self.instruction_pointer],
+        )
+    }
+
+    const fn overflow(&self) -> Error {
+        Error::Overflow {
+            address: self.instruction_pointer,
+        }
+    }
+
+    fn address(&self, target: i64) -> Result<usize, Error> {
+        usize::try_from(target).map_err(|_| Error::NegativeAddress {
+            address: self.instruction_pointer,
+            target,
+        })
+    }
+
+    /// Where parameter `n` (from 0) points, for position and relative modes
+    fn param_address(&self, instruction: Instruction, n: usize) -> Result<usize, Error> {
+        let raw = self.memory[self.instruction_pointer + 1 + n];
+        match instruction.modes[n] {
+            Mode::Position => self.address(raw),
+            Mode::Relative => self.address(
+                self.relative_base
+                    .checked_add(raw)
+                    .ok_or_else(|| self.overflow())?,
+            ),
+            Mode::Immediate => Err(Error::ImmediateWrite {
+                address: self.instruction_pointer,
+            }),
+        }
+    }
+
+    fn param(&self, instruction: Instruction, n: usize) -> Result<i64, Error> {
+        match instruction.modes[n] {
+            Mode::Immediate => Ok(self.memory[self.instruction_pointer + 1 + n]),
+            Mode::Position | Mode::Relative => Ok(self.memory[self.param_address(instruction, n)?]),
+        }
+    }
+
+    /// Runs one instruction, returning the [`RunState`] if it was an output or something that
+    /// stops the machine. Halting and waiting for input don't move the instruction pointer, so
+    /// stepping again just stops again (until there's input).
+    ///
+    /// # Errors
+    /// If the instruction is invalid or uses a negative address. Nothing is changed when that
+    /// happens.
+    pub fn step(&mut self) -> Result<Option<RunState>, Error> {
+        let instruction = self.current_instruction()?;
+        self.execute(instruction).map(|(state, _)| state)
+    }
+
+    /// Runs `instruction` as if it had been decoded from the instruction pointer, returning the
+    /// state for [`Self::step`] and the cell it wrote to, if any
+    fn execute(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<(Option<RunState>, Option<Written>), Error> {
+        let mut next = self.instruction_pointer + instruction.size();
+        let param = |n| self.param(instruction, n);
+        let mut state = None;
+        let mut written = None;
+        match instruction.op {
+            Op::Add | Op::Mul | Op::LessThan | Op::Equals => {
+                let (a, b) = (param(0)?, param(1)?);
+                let value = match instruction.op {
+                    Op::Add => a.checked_add(b).ok_or_else(|| self.overflow())?,
+                    Op::Mul => a.checked_mul(b).ok_or_else(|| self.overflow())?,
+                    Op::LessThan => i64::from(a < b),
+                    _ => i64::from(a == b),
+                };
+                let dest = self.param_address(instruction, 2)?;
+                written = Some(Written {
+                    address: dest,
+                    old: std::mem::replace(&mut self.memory[dest], value),
+                });
+            }
+            Op::Input => {
+                let dest = self.param_address(instruction, 0)?;
+                let Some(value) = self.inputs.pop_front() else {
+                    return Ok((Some(RunState::NeedInput), None));
+                };
+                written = Some(Written {
+                    address: dest,
+                    old: std::mem::replace(&mut self.memory[dest], value),
+                });
+            }
+            Op::Output => state = Some(RunState::Output(param(0)?)),
+            Op::JumpIfTrue | Op::JumpIfFalse => {
+                let (condition, target) = (param(0)?, param(1)?);
+                if (condition != 0) == (instruction.op == Op::JumpIfTrue) {
+                    next = self.address(target)?;
+                }
+            }
+            Op::AdjustBase => {
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(param(0)?)
+                    .ok_or_else(|| self.overflow())?;
+            }
+            Op::Halt => return Ok((Some(RunState::Halted), None)),
+        }
+        self.instruction_pointer = next;
+        Ok((state, written))
+    }
+
+    /// Steps until the program outputs something, needs input it doesn't have, or halts
+    ///
+    /// # Errors
+    /// See [`Self::step`]
+    pub fn run(&mut self) -> Result<RunState, Error> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Saves the whole machine, memory, registers and queued inputs. Cheap, since memory is only
+    /// copied as it's written to.
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.clone())
+    }
+
+    /// Puts the machine back to how it was at `snapshot`
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.clone_from(&snapshot.0);
+    }
+
+    /// A copy of the machine that's been given `inputs` and run until it wants more or halts,
+    /// along with what it output on the way. This one is left as it was.
+    ///
+    /// # Errors
+    /// See [`Self::step`]
+    pub fn branch<I: IntoIterator<Item = i64>>(
+        &self,
+        inputs: I,
+    ) -> Result<(Self, Vec<i64>), Error> {
+        let mut branch = self.clone();
+        branch.push_inputs(inputs);
+        let mut outputs = vec![];
+        loop {
+            match branch.run()? {
+                RunState::Output(value) => outputs.push(value),
+                RunState::NeedInput | RunState::Halted => return Ok((branch, outputs)),
+            }
+        }
+    }
+
+    /// Runs until the program halts, returning everything it output along the way
+    ///
+    /// # Errors
+    /// See [`Self::step`], and [`Error::NoInput`] if it runs out of queued input
+    pub fn run_to_halt(&mut self) -> Result<Vec<i64>, Error> {
+        let mut outputs = vec![];
+        loop {
+            match self.run()? {
+                RunState::Output(value) => outputs.push(value),
+                RunState::Halted => return Ok(outputs),
+                RunState::NeedInput => {
+                    return Err(Error::NoInput {
+                        address: self.instruction_pointer,
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl From<Vec<i64>> for IntCode {
+    fn from(program: Vec<i64>) -> Self {
+        Self::new(program)
+    }
+}
+
+/// Comma separated, surrounding whitespace is fine
+impl FromStr for IntCode {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let program = s
+            .trim()
+            .split(',')
+            .map(|n| n.trim().parse())
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(program))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_with_inputs(program: &str, inputs: &[i64]) -> Vec<i64> {
+        let mut intcode: IntCode = program.parse().unwrap();
+        intcode.push_inputs(inputs.iter().copied());
+        intcode.run_to_halt().unwrap()
+    }
+
+    #[test]
+    fn test_add_mul() {
+        let mut intcode: IntCode = "1,9,10,3,2,3,11,0,99,30,40,50".parse().unwrap();
+        intcode.run_to_halt().unwrap();
+        assert_eq!(intcode.memory[0], 3500);
+        let mut intcode: IntCode = "1002,4,3,4,33".parse().unwrap();
+        intcode.run_to_halt().unwrap();
+        assert_eq!(intcode.memory[4], 99);
+    }
+
+    #[test]
+    fn test_compare_and_jump() {
+        // day 5: is the input equal to / less than 8, position and immediate mode
+        for program in ["3,9,8,9,10,9,4,9,99,-1,8", "3,3,1108,-1,8,3,4,3,99"] {
+            assert_eq!(run_with_inputs(program, &[8]), [1]);
+            assert_eq!(run_with_inputs(program, &[7]), [0]);
+        }
+        assert_eq!(run_with_inputs("3,9,7,9,10,9,4,9,99,-1,8", &[5]), [1]);
+        // is the input non-zero, using jumps
+        let jumps = "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9";
+        assert_eq!(run_with_inputs(jumps, &[0]), [0]);
+        assert_eq!(run_with_inputs(jumps, &[3]), [1]);
+    }
+
+    #[test]
+    fn test_relative_base_and_big_numbers() {
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let expected: Vec<i64> = quine.split(',').map(|n| n.parse().unwrap()).collect();
+        assert_eq!(run_with_inputs(quine, &[]), expected);
+        assert_eq!(
+            run_with_inputs("1102,34915192,34915192,7,4,7,99,0", &[]),
+            [1_219_070_632_396_864]
+        );
+        assert_eq!(
+            run_with_inputs("104,1125899906842624,99", &[]),
+            [1_125_899_906_842_624]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut intcode: IntCode = "1,0,0,0,42".parse().unwrap();
+        assert_eq!(
+            intcode.run_to_halt(),
+            Err(Error::UnknownOpcode {
+                address: 4,
+                value: 42
+            })
+        );
+        let mut intcode: IntCode = "1,-1,0,0,99".parse().unwrap();
+        assert_eq!(
+            intcode.run_to_halt(),
+            Err(Error::NegativeAddress {
+                address: 0,
+                target: -1
+            })
+        );
+        let mut intcode: IntCode = "3,0,99".parse().unwrap();
+        assert_eq!(intcode.run_to_halt(), Err(Error::NoInput { address: 0 }));
+        // nothing changed, so it can carry on once there's input
+        intcode.push_input(5);
+        intcode.run_to_halt().unwrap();
+        assert_eq!(intcode.memory[0], 5);
+        assert!("1,x,3".parse::<IntCode>().is_err());
+    }
+
+    const AMPLIFIERS: &str = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+    const FEEDBACK_AMPLIFIERS: &str =
+        "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+
+    #[test]
+    fn test_pause_for_input() {
+        let mut intcode: IntCode = "3,0,4,0,3,0,4,0,99".parse().unwrap();
+        assert_eq!(intcode.run(), Ok(RunState::NeedInput));
+        assert_eq!(intcode.run(), Ok(RunState::NeedInput));
+        intcode.push_input(7);
+        assert_eq!(intcode.run(), Ok(RunState::Output(7)));
+        intcode.push_inputs([8, 9]);
+        assert_eq!(intcode.run(), Ok(RunState::Output(8)));
+        assert_eq!(intcode.pending_inputs(), 1);
+        assert_eq!(intcode.run(), Ok(RunState::Halted));
+        assert_eq!(intcode.run(), Ok(RunState::Halted));
+    }
+
+    #[test]
+    fn test_amplifier_chain() {
+        // day 7 example, phases 4,3,2,1,0
+        let signal = [4, 3, 2, 1, 0].into_iter().fold(0, |signal, phase| {
+            let mut amplifier: IntCode = AMPLIFIERS.parse().unwrap();
+            amplifier.push_inputs([phase, signal]);
+            amplifier.run_to_halt().unwrap()[0]
+        });
+        assert_eq!(signal, 43210);
+    }
+
+    #[test]
+    fn test_feedback_loop() {
+        let mut amplifiers: Vec<IntCode> = [9, 8, 7, 6, 5]
+            .into_iter()
+            .map(|phase| {
+                let mut amplifier: IntCode = FEEDBACK_AMPLIFIERS.parse().unwrap();
+                amplifier.push_input(phase);
+                amplifier
+            })
+            .collect();
+        let mut signal = 0;
+        'feedback: loop {
+            for amplifier in &mut amplifiers {
+                amplifier.push_input(signal);
+                match amplifier.run().unwrap() {
+                    RunState::Output(value) => signal = value,
+                    RunState::Halted => break 'feedback,
+                    RunState::NeedInput => unreachable!(),
+                }
+            }
+        }
+        assert_eq!(signal, 139_629_729);
+    }
+
+    #[test]
+    fn test_snapshot_and_branch() {
+        let mut intcode: IntCode = "3,0,4,0,3,0,4,0,99".parse().unwrap();
+        let snapshot = intcode.snapshot();
+        intcode.push_inputs([1, 2]);
+        assert_eq!(intcode.run_to_halt(), Ok(vec![1, 2]));
+        intcode.restore(&snapshot);
+        assert_eq!(intcode.instruction_pointer(), 0);
+        assert_eq!(intcode.memory[0], 3);
+
+        let (first, outputs) = intcode.branch([5]).unwrap();
+        assert_eq!(outputs, [5]);
+        assert_eq!(first.instruction_pointer(), 4);
+        let (mut second, outputs) = first.branch([6]).unwrap();
+        assert_eq!(outputs, [6]);
+        assert_eq!(second.run().unwrap(), RunState::Halted);
+        // the originals are untouched
+        assert_eq!(intcode.snapshot(), snapshot);
+        assert_eq!(first.memory[0], 5);
+    }
+}
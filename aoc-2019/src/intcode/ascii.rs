@@ -0,0 +1,182 @@
+//! Talking to Intcode programs that speak ASCII, one line at a time.
+//!
+//! Outputs in the ASCII range become text, anything else (usually a final answer too big to be a
+//! character) is kept as a number. Commands go in as characters with a newline on the end.
+
+use std::fmt::Write;
+
+use super::{Error, IntCode, RunState};
+
+/// What a program said before it wanted more input or halted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Response {
+    pub text: String,
+    /// Outputs that weren't ASCII
+    pub values: Vec<i64>,
+    pub halted: bool,
+}
+
+impl Response {
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Ascii {
+    intcode: IntCode,
+}
+
+impl Ascii {
+    #[must_use]
+    pub const fn new(intcode: IntCode) -> Self {
+        Self { intcode }
+    }
+
+    #[must_use]
+    pub const fn intcode(&self) -> &IntCode {
+        &self.intcode
+    }
+
+    pub const fn intcode_mut(&mut self) -> &mut IntCode {
+        &mut self.intcode
+    }
+
+    /// Queues `command` and a newline
+    pub fn send(&mut self, command: &str) {
+        self.intcode.push_inputs(
+            command
+                .chars()
+                .chain(['\n'])
+                .map(|c| i64::from(u32::from(c))),
+        );
+    }
+
+    /// Runs until the program wants input it hasn't got, or halts
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn read(&mut self) -> Result<Response, Error> {
+        let mut response = Response::default();
+        loop {
+            match self.intcode.run()? {
+                RunState::Output(value) => match u8::try_from(value) {
+                    Ok(c) if c.is_ascii() => response.text.push(char::from(c)),
+                    _ => response.values.push(value),
+                },
+                RunState::NeedInput => return Ok(response),
+                RunState::Halted => {
+                    response.halted = true;
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// Sends each command in `script` in turn, returning everything that happened as if it had
+    /// been typed in: the program's text, with each command after a `> ` and non-ASCII values on
+    /// their own lines. Blank lines and lines starting with `#` are skipped. Stops early if the
+    /// program halts.
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn run_script(&mut self, script: &str) -> Result<Response, Error> {
+        let mut transcript = Response::default();
+        let add = |transcript: &mut Response, response: Response| {
+            transcript.text += &response.text;
+            for value in &response.values {
+                let _ = writeln!(transcript.text, "{value}");
+            }
+            transcript.values.extend(response.values);
+            transcript.halted = response.halted;
+        };
+        add(&mut transcript, self.read()?);
+        let commands = script
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for command in commands {
+            if transcript.halted {
+                break;
+            }
+            let _ = writeln!(transcript.text, "> {command}");
+            self.send(command);
+            add(&mut transcript, self.read()?);
+        }
+        Ok(transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assemble::assemble;
+
+    /// Prints "ok?", then echoes lines back until one starts with a '.', then outputs 1000
+    fn echo() -> Ascii {
+        let memory = assemble(
+            "
+                arb  prompt
+            print:
+                jz   [rb], read
+                out  [rb]
+                arb  1
+                jz   0, print
+            read:
+                in   [c]
+                eq   [c], 46, [t]       ; '.'
+                jnz  [t], done
+                out  [c]
+                eq   [c], 10, [t]       ; newline
+                jz   [t], read
+                arb  -4
+                jz   0, print
+            done:
+                out  1000
+                hlt
+            prompt: data 111, 107, 63, 10, 0
+            c:      data 0
+            t:      data 0
+            ",
+        )
+        .unwrap();
+        Ascii::new(IntCode::new(memory))
+    }
+
+    #[test]
+    fn test_send_and_read() {
+        let mut ascii = echo();
+        assert_eq!(ascii.read().unwrap().text, "ok?\n");
+        ascii.send("hello");
+        let response = ascii.read().unwrap();
+        assert_eq!(response.lines().collect::<Vec<_>>(), ["hello", "ok?"]);
+        assert!(!response.halted);
+        ascii.send(".");
+        assert_eq!(
+            ascii.read(),
+            Ok(Response {
+                text: String::new(),
+                values: vec![1000],
+                halted: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_script() {
+        let script = "
+            # say hi
+            hi there
+
+            .
+            never sent
+        ";
+        let transcript = echo().run_script(script).unwrap();
+        assert_eq!(
+            transcript.text,
+            "ok?\n> hi there\nhi there\nok?\n> .\n1000\n"
+        );
+        assert_eq!(transcript.values, [1000]);
+        assert!(transcript.halted);
+    }
+}
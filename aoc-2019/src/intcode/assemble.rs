@@ -0,0 +1,340 @@
+//! A small assembler, for writing Intcode test programs without counting addresses by hand.
+//!
+//! One instruction per line, using the same syntax the disassembler prints:
+//!
+//! ```text
+//! ; read numbers until a 0, then output their sum
+//! loop:   in   [n]
+//!         jz   [n], done
+//!         add  [sum], [n], [sum]
+//!         jz   0, loop            ; always jumps
+//! done:   out  [sum]
+//!         hlt
+//! n:      data 0
+//! sum:    data 0
+//! ```
+//!
+//! Operands are `5` (immediate), `[5]` (position) or `[rb+5]` (relative). Anywhere a number can
+//! go, so can a label, optionally with an offset like `table+2`. `data` puts raw values into
+//! memory.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use super::{Instruction, Mode, Op};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssembleErrorKind {
+    UnknownMnemonic(String),
+    WrongParamCount {
+        expected: usize,
+        found: usize,
+    },
+    BadOperand(String),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    /// An immediate operand where the op writes
+    ImmediateWrite,
+}
+
+/// What went wrong, and on which (1-based) line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub kind: AssembleErrorKind,
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AssembleErrorKind::UnknownMnemonic(mnemonic) => {
+                write!(f, "unknown mnemonic {mnemonic:?}")
+            }
+            AssembleErrorKind::WrongParamCount { expected, found } => {
+                write!(f, "expected {expected} operands, found {found}")
+            }
+            AssembleErrorKind::BadOperand(operand) => write!(f, "can't parse operand {operand:?}"),
+            AssembleErrorKind::UndefinedLabel(label) => write!(f, "undefined label {label:?}"),
+            AssembleErrorKind::DuplicateLabel(label) => write!(f, "label {label:?} defined twice"),
+            AssembleErrorKind::ImmediateWrite => write!(f, "can't write to an immediate operand"),
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// A number, or a label plus an offset
+#[derive(Debug, Clone)]
+enum Value {
+    Number(i64),
+    Label(String, i64),
+}
+
+impl Value {
+    fn parse(text: &str) -> Option<Self> {
+        if let Ok(number) = text.parse() {
+            return Some(Self::Number(number));
+        }
+        let (label, offset) = match text.find(['+', '-']) {
+            Some(idx) => (
+                &text[..idx],
+                text[idx..].trim_start_matches('+').parse().ok()?,
+            ),
+            None => (text, 0),
+        };
+        let is_label = label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        is_label.then(|| Self::Label(label.to_string(), offset))
+    }
+
+    fn resolve(&self, labels: &HashMap<String, usize>) -> Result<i64, AssembleErrorKind> {
+        match self {
+            Self::Number(number) => Ok(*number),
+            Self::Label(label, offset) => labels
+                .get(label)
+                .and_then(|&address| i64::try_from(address).ok())
+                .ok_or_else(|| AssembleErrorKind::UndefinedLabel(label.clone()))?
+                .checked_add(*offset)
+                .ok_or_else(|| AssembleErrorKind::BadOperand(format!("{label}{offset:+}"))),
+        }
+    }
+}
+
+fn parse_operand(text: &str) -> Result<(Mode, Value), AssembleErrorKind> {
+    let bad = || AssembleErrorKind::BadOperand(text.to_string());
+    let Some(inner) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) else {
+        return Ok((Mode::Immediate, Value::parse(text).ok_or_else(bad)?));
+    };
+    let inner = inner.trim();
+    // `rb` on its own or followed by an offset, so labels like `rbuf` are still positions
+    let relative = inner.strip_prefix("rb").filter(|rest| {
+        rest.is_empty() || rest.starts_with(['+', '-']) || rest.starts_with(char::is_whitespace)
+    });
+    let Some(offset) = relative else {
+        return Ok((Mode::Position, Value::parse(inner).ok_or_else(bad)?));
+    };
+    let offset = offset.trim().replace(' ', "");
+    let offset = if offset.is_empty() {
+        Value::Number(0)
+    } else {
+        let offset = offset.strip_prefix('+').unwrap_or(&offset);
+        Value::parse(offset).ok_or_else(bad)?
+    };
+    Ok((Mode::Relative, offset))
+}
+
+/// Instructions and data before labels are filled in
+enum Item {
+    Instruction {
+        op: Op,
+        operands: Vec<(Mode, Value)>,
+    },
+    Data(Vec<Value>),
+}
+
+impl Item {
+    const fn size(&self) -> usize {
+        match self {
+            Self::Instruction { operands, .. } => 1 + operands.len(),
+            Self::Data(values) => values.len(),
+        }
+    }
+}
+
+fn parse_item(text: &str) -> Result<Item, AssembleErrorKind> {
+    let (mnemonic, rest) = text
+        .split_once(char::is_whitespace)
+        .map_or((text, ""), |(mnemonic, rest)| (mnemonic, rest.trim()));
+    let operands: Vec<&str> = if rest.is_empty() {
+        vec![]
+    } else {
+        rest.split(',').map(str::trim).collect()
+    };
+    if mnemonic == "data" {
+        let values = operands
+            .iter()
+            .map(|&operand| {
+                Value::parse(operand).ok_or_else(|| AssembleErrorKind::BadOperand(operand.into()))
+            })
+            .collect::<Result<_, _>>()?;
+        return Ok(Item::Data(values));
+    }
+    let op = Op::from_mnemonic(mnemonic)
+        .ok_or_else(|| AssembleErrorKind::UnknownMnemonic(mnemonic.to_string()))?;
+    if operands.len() != op.param_count() {
+        return Err(AssembleErrorKind::WrongParamCount {
+            expected: op.param_count(),
+            found: operands.len(),
+        });
+    }
+    let operands: Vec<_> = operands
+        .into_iter()
+        .map(parse_operand)
+        .collect::<Result<_, _>>()?;
+    if op.writes()
+        && operands
+            .last()
+            .is_some_and(|(mode, _)| *mode == Mode::Immediate)
+    {
+        return Err(AssembleErrorKind::ImmediateWrite);
+    }
+    Ok(Item::Instruction { op, operands })
+}
+
+/// Turns assembly into Intcode memory
+///
+/// # Errors
+/// On the first line that doesn't make sense, see [`AssembleErrorKind`]
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssembleError> {
+    // first pass: work out where everything goes
+    let mut labels = HashMap::new();
+    let mut items = vec![];
+    let mut address = 0;
+    for (idx, line) in source.lines().enumerate() {
+        let line_number = idx + 1;
+        let error = |kind| AssembleError {
+            line: line_number,
+            kind,
+        };
+        let mut text = line.split(';').next().unwrap_or_default().trim();
+        while let Some((label, rest)) = text.split_once(':') {
+            let label = label.trim();
+            if !matches!(Value::parse(label), Some(Value::Label(_, 0))) {
+                return Err(error(AssembleErrorKind::BadOperand(label.to_string())));
+            }
+            if labels.insert(label.to_string(), address).is_some() {
+                return Err(error(AssembleErrorKind::DuplicateLabel(label.to_string())));
+            }
+            text = rest.trim();
+        }
+        if text.is_empty() {
+            continue;
+        }
+        let item = parse_item(text).map_err(error)?;
+        address += item.size();
+        items.push((line_number, item));
+    }
+
+    // second pass: fill in the labels
+    let mut memory = Vec::with_capacity(address);
+    for (line, item) in items {
+        let error = |kind| AssembleError { line, kind };
+        match item {
+            Item::Instruction { op, operands } => {
+                let mut modes = [Mode::Position; 3];
+                for (mode, (operand_mode, _)) in modes.iter_mut().zip(&operands) {
+                    *mode = *operand_mode;
+                }
+                memory.push(Instruction { op, modes }.encode());
+                for (_, value) in &operands {
+                    memory.push(value.resolve(&labels).map_err(error)?);
+                }
+            }
+            Item::Data(values) => {
+                for value in &values {
+                    memory.push(value.resolve(&labels).map_err(error)?);
+                }
+            }
+        }
+    }
+    Ok(memory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::disassemble::disassemble;
+
+    #[test]
+    fn test_assemble() {
+        let memory = assemble(
+            "
+            start: arb 1         ; the quine from day 9
+                   out [rb-1]
+                   add [100], 1, [100]
+                   eq  [100], 16, [101]
+                   jz  [101], start
+                   hlt
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            memory,
+            [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99]
+        );
+    }
+
+    #[test]
+    fn test_labels_and_data() {
+        let memory = assemble(
+            "
+                out [table+1]
+                out [rb]
+                out [rbuf]
+                hlt
+            table: data 7, -8, table
+            rbuf: data 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(memory, [4, 8, 204, 0, 4, 10, 99, 7, -8, 7, 0]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = "
+            L0:
+                in   [20]
+                lt   [20], 10, [21]
+                jnz  [21], L0
+                mul  [rb+3], -2, [rb-4]
+                hlt
+        ";
+        let memory = assemble(source).unwrap();
+        let listing = disassemble(&memory).to_string();
+        // drop the address column
+        let listing: Vec<&str> = listing
+            .lines()
+            .map(|line| line.trim_start().trim_start_matches(char::is_numeric))
+            .collect();
+        assert_eq!(assemble(&listing.join("\n")).unwrap(), memory);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source| assemble(source).unwrap_err();
+        assert_eq!(
+            error("hlt\nfoo 1"),
+            AssembleError {
+                line: 2,
+                kind: AssembleErrorKind::UnknownMnemonic("foo".into())
+            }
+        );
+        assert_eq!(
+            error("add 1, 2").kind,
+            AssembleErrorKind::WrongParamCount {
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(error("in 5").kind, AssembleErrorKind::ImmediateWrite);
+        assert_eq!(
+            error("out [nowhere]").kind,
+            AssembleErrorKind::UndefinedLabel("nowhere".into())
+        );
+        assert_eq!(
+            error("a: hlt\na: hlt").kind,
+            AssembleErrorKind::DuplicateLabel("a".into())
+        );
+        assert_eq!(
+            error("out [rb*2]").kind,
+            AssembleErrorKind::BadOperand("[rb*2]".into())
+        );
+        // the label's address plus the offset doesn't fit in an i64
+        assert_eq!(
+            error("out x+9223372036854775807\nx: hlt").kind,
+            AssembleErrorKind::BadOperand("x+9223372036854775807".into())
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Searching over machine states, for puzzles where the program is the only map there is.
+//!
+//! Comparing and hashing whole machines works but is slow and usually too strict, since two
+//! machines in the same place can differ in memory that doesn't matter. [`Branch`] pairs a
+//! machine with a key that stands in for it, like a droid's position, so it can go straight
+//! into [`aoc_helpers::math::dijkstra`] and friends.
+
+use std::hash::{Hash, Hasher};
+
+use super::{Error, IntCode};
+
+/// A machine that's compared and hashed by `key` alone
+#[derive(Debug, Clone)]
+pub struct Branch<K> {
+    pub key: K,
+    pub intcode: IntCode,
+}
+
+impl<K> Branch<K> {
+    pub const fn new(key: K, intcode: IntCode) -> Self {
+        Self { key, intcode }
+    }
+
+    /// Feeds `inputs` to a copy of the machine and runs it until it wants more or halts, then
+    /// works out the new key from the old one and what the machine output
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn then<I, F>(&self, inputs: I, key: F) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = i64>,
+        F: FnOnce(&K, &[i64]) -> K,
+    {
+        let (intcode, outputs) = self.intcode.branch(inputs)?;
+        Ok(Self::new(key(&self.key, &outputs), intcode))
+    }
+}
+
+impl<K: PartialEq> PartialEq for Branch<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Eq> Eq for Branch<K> {}
+
+impl<K: Hash> Hash for Branch<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assemble::assemble;
+    use aoc_helpers::math::dijkstra;
+
+    /// A droid in a corridor that can move 1 or 2 forwards, and reports 0 for a wall (and doesn't
+    /// move), 1 for open floor and 2 for the goal
+    const CORRIDOR: &str = "
+        loop:   in   [d]
+                add  [p], [d], [q]
+                arb  [q]
+                add  [rb+maze], 0, [cell]
+                mul  [q], -1, [q]
+                arb  [q]
+                out  [cell]
+                jz   [cell], loop
+                add  [p], [d], [p]
+                jz   0, loop
+        p:      data 0
+        d:      data 0
+        q:      data 0
+        cell:   data 0
+        maze:   data 1, 1, 1, 0, 1, 0, 1, 0, 1, 2
+    ";
+
+    #[test]
+    fn test_search() {
+        let droid = IntCode::new(assemble(CORRIDOR).unwrap());
+        // (position, last status)
+        let start = Branch::new((0, 1), droid);
+        let (path, cost) = dijkstra(
+            &start,
+            |branch: &Branch<(i64, i64)>| {
+                [1, 2]
+                    .into_iter()
+                    .map(|step| {
+                        branch
+                            .then([step], |&(position, _), outputs| {
+                                (position + step, outputs[0])
+                            })
+                            .unwrap()
+                    })
+                    .filter(|branch| branch.key.1 != 0)
+                    .map(|branch| (branch, 1))
+                    .collect::<Vec<_>>()
+            },
+            |branch| branch.key.1 == 2,
+        )
+        .unwrap();
+        assert_eq!(cost, 5);
+        let positions: Vec<i64> = path.iter().map(|branch| branch.key.0).collect();
+        assert_eq!(positions, [0, 2, 4, 6, 8, 9]);
+        // each machine only copied the memory it wrote to
+        let last = &path.last().unwrap().intcode;
+        assert_eq!(last.memory.shared_chunks(&start.intcode.memory), 0);
+    }
+}
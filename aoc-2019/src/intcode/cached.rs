@@ -0,0 +1,229 @@
+//! A faster [`IntCode`] for programs that run for a long time: each instruction is decoded once
+//! and then looked up, until something writes over it. Can also count what ran, for finding the
+//! hot loops.
+//!
+//! `cargo run --release -p aoc-2019 --bin intcode-bench` compares it with the plain interpreter.
+
+use super::{Error, Instruction, IntCode, Op, RunState};
+
+/// How many times each op and each address ran
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    ops: [u64; Op::ALL.len()],
+    addresses: Vec<u64>,
+}
+
+impl Profile {
+    fn record(&mut self, address: usize, op: Op) {
+        self.ops[op as usize] += 1;
+        if address >= self.addresses.len() {
+            self.addresses.resize(address + 1, 0);
+        }
+        self.addresses[address] += 1;
+    }
+
+    #[must_use]
+    pub const fn op_count(&self, op: Op) -> u64 {
+        self.ops[op as usize]
+    }
+
+    #[must_use]
+    pub fn address_count(&self, address: usize) -> u64 {
+        self.addresses.get(address).copied().unwrap_or(0)
+    }
+
+    /// Instructions run in total
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.ops.iter().sum()
+    }
+
+    /// The `n` addresses that ran the most, most first
+    #[must_use]
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut counts: Vec<(usize, u64)> = self
+            .addresses
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        counts.sort_by_key(|&(address, count)| (std::cmp::Reverse(count), address));
+        counts.truncate(n);
+        counts
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CachedIntCode {
+    intcode: IntCode,
+    /// The decoded instruction at each address, if it's been decoded and not written to since
+    cache: Vec<Option<Instruction>>,
+    profile: Option<Profile>,
+}
+
+impl CachedIntCode {
+    #[must_use]
+    pub fn new(intcode: IntCode) -> Self {
+        Self {
+            cache: vec![None; intcode.memory.len()],
+            intcode,
+            profile: None,
+        }
+    }
+
+    /// Counts every instruction that runs, see [`Self::profile`]
+    #[must_use]
+    pub fn with_profile(self) -> Self {
+        Self {
+            profile: Some(Profile::default()),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn intcode(&self) -> &IntCode {
+        &self.intcode
+    }
+
+    /// Anything could change through this, so the whole cache is thrown away
+    pub fn intcode_mut(&mut self) -> &mut IntCode {
+        self.cache.fill(None);
+        &mut self.intcode
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> IntCode {
+        self.intcode
+    }
+
+    #[must_use]
+    pub const fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.intcode.push_input(value);
+    }
+
+    pub fn push_inputs<I: IntoIterator<Item = i64>>(&mut self, values: I) {
+        self.intcode.push_inputs(values);
+    }
+
+    fn decode(&mut self, address: usize) -> Result<Instruction, Error> {
+        if let Some(Some(instruction)) = self.cache.get(address) {
+            return Ok(*instruction);
+        }
+        let instruction = self.intcode.current_instruction()?;
+        if address >= self.cache.len() {
+            self.cache.resize(address + 1, None);
+        }
+        self.cache[address] = Some(instruction);
+        Ok(instruction)
+    }
+
+    /// Same as [`IntCode::step`]
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn step(&mut self) -> Result<Option<RunState>, Error> {
+        let address = self.intcode.instruction_pointer();
+        let instruction = self.decode(address)?;
+        let (state, written) = self.intcode.execute(instruction)?;
+        if let Some(slot) = written.and_then(|written| self.cache.get_mut(written.address)) {
+            *slot = None;
+        }
+        if let Some(profile) = &mut self.profile {
+            // waiting for input didn't actually run anything
+            if state != Some(RunState::NeedInput) {
+                profile.record(address, instruction.op);
+            }
+        }
+        Ok(state)
+    }
+
+    /// Same as [`IntCode::run`]
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn run(&mut self) -> Result<RunState, Error> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// Same as [`IntCode::run_to_halt`]
+    ///
+    /// # Errors
+    /// See [`IntCode::run_to_halt`]
+    pub fn run_to_halt(&mut self) -> Result<Vec<i64>, Error> {
+        let mut outputs = vec![];
+        loop {
+            match self.run()? {
+                RunState::Output(value) => outputs.push(value),
+                RunState::Halted => return Ok(outputs),
+                RunState::NeedInput => {
+                    return Err(Error::NoInput {
+                        address: self.intcode.instruction_pointer(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl From<IntCode> for CachedIntCode {
+    fn from(intcode: IntCode) -> Self {
+        Self::new(intcode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assemble::assemble;
+
+    #[test]
+    fn test_self_modifying() {
+        // the add at `patch` gets turned into a mul after running once
+        let memory = assemble(
+            "
+            loop:
+                add  [n], -1, [n]
+            patch:
+                add  [x], [x], [x]
+                add  0, 2, [patch]
+                jnz  [n], loop
+                out  [x]
+                hlt
+            n:  data 3
+            x:  data 3
+            ",
+        )
+        .unwrap();
+        let plain = IntCode::new(memory.clone()).run_to_halt();
+        // 3 + 3, then squared twice
+        assert_eq!(plain, Ok(vec![1296]));
+        assert_eq!(
+            CachedIntCode::new(IntCode::new(memory)).run_to_halt(),
+            plain
+        );
+    }
+
+    #[test]
+    fn test_profile() {
+        let quine: IntCode = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99"
+            .parse()
+            .unwrap();
+        let mut cached = CachedIntCode::new(quine).with_profile();
+        assert_eq!(cached.run_to_halt().unwrap().len(), 16);
+        let profile = cached.profile().unwrap();
+        assert_eq!(profile.op_count(Op::Output), 16);
+        assert_eq!(profile.op_count(Op::Halt), 1);
+        assert_eq!(profile.address_count(12), 16);
+        assert_eq!(profile.total(), 16 * 5 + 1);
+        assert_eq!(profile.hottest(2), [(0, 16), (2, 16)]);
+    }
+}
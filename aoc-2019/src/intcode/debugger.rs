@@ -0,0 +1,573 @@
+//! Running an Intcode program a bit at a time and poking at it in between.
+//!
+//! The [`Debugger`] owns the machine and remembers what every step changed, so it can step
+//! backwards as well as forwards. [`Command`] is the text interface the `intcode-debugger` binary
+//! uses, kept here so it can be tested.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+use super::disassemble::Line;
+use super::{Error, IntCode, Op, RunState, Written};
+
+/// How many steps back [`Debugger::back`] can go
+pub const HISTORY_LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Breakpoint {
+    /// Stop before running the instruction at this address
+    Address(usize),
+    /// Stop before running any instruction with this op
+    Op(Op),
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Address(address) => write!(f, "{address}"),
+            Self::Op(op) => write!(f, "{}", op.mnemonic()),
+        }
+    }
+}
+
+/// An address or a mnemonic
+impl FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Self::Address)
+            .ok()
+            .or_else(|| Op::from_mnemonic(s).map(Self::Op))
+            .ok_or_else(|| format!("not an address or a mnemonic: {s:?}"))
+    }
+}
+
+/// Why the debugger gave control back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Did all the steps asked for
+    Stepped,
+    Breakpoint(Breakpoint),
+    /// The last step changed a watched cell
+    Watchpoint {
+        address: usize,
+        old: i64,
+        new: i64,
+    },
+    NeedInput,
+    Halted,
+}
+
+impl Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stepped => write!(f, "stepped"),
+            Self::Breakpoint(breakpoint) => write!(f, "breakpoint {breakpoint}"),
+            Self::Watchpoint { address, old, new } => {
+                write!(f, "watchpoint [{address}]: {old} -> {new}")
+            }
+            Self::NeedInput => write!(f, "waiting for input"),
+            Self::Halted => write!(f, "halted"),
+        }
+    }
+}
+
+/// What one step changed, enough to put the machine back how it was
+#[derive(Debug, Clone, Copy)]
+struct Undo {
+    instruction_pointer: usize,
+    relative_base: i64,
+    memory_len: usize,
+    written: Option<Written>,
+    /// The input the step read
+    input: Option<i64>,
+    /// How many outputs there were before the step
+    outputs: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    intcode: IntCode,
+    breakpoints: BTreeSet<Breakpoint>,
+    watchpoints: BTreeSet<usize>,
+    outputs: Vec<i64>,
+    /// How to undo each step, most recent last
+    history: VecDeque<Undo>,
+}
+
+impl Debugger {
+    #[must_use]
+    pub const fn new(intcode: IntCode) -> Self {
+        Self {
+            intcode,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            outputs: vec![],
+            history: VecDeque::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn intcode(&self) -> &IntCode {
+        &self.intcode
+    }
+
+    /// For queueing input or patching memory. Changes made here aren't undone by [`Self::back`].
+    pub const fn intcode_mut(&mut self) -> &mut IntCode {
+        &mut self.intcode
+    }
+
+    /// Everything output so far
+    #[must_use]
+    pub fn outputs(&self) -> &[i64] {
+        &self.outputs
+    }
+
+    #[must_use]
+    pub const fn breakpoints(&self) -> &BTreeSet<Breakpoint> {
+        &self.breakpoints
+    }
+
+    #[must_use]
+    pub const fn watchpoints(&self) -> &BTreeSet<usize> {
+        &self.watchpoints
+    }
+
+    /// Returns false if it was already set
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        self.breakpoints.insert(breakpoint)
+    }
+
+    /// Returns false if it wasn't set
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) -> bool {
+        self.breakpoints.remove(&breakpoint)
+    }
+
+    /// Returns false if it was already watched
+    pub fn watch(&mut self, address: usize) -> bool {
+        self.watchpoints.insert(address)
+    }
+
+    /// Returns false if it wasn't watched
+    pub fn unwatch(&mut self, address: usize) -> bool {
+        self.watchpoints.remove(&address)
+    }
+
+    /// How many steps [`Self::back`] can undo
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The first breakpoint for the instruction about to run
+    fn breakpoint_hit(&self) -> Option<Breakpoint> {
+        let address = Breakpoint::Address(self.intcode.instruction_pointer());
+        if self.breakpoints.contains(&address) {
+            return Some(address);
+        }
+        let op = Breakpoint::Op(self.intcode.current_instruction().ok()?.op);
+        self.breakpoints.contains(&op).then_some(op)
+    }
+
+    /// Runs one instruction, returning why to stop if there's a reason to
+    fn step_one(&mut self) -> Result<Option<Stop>, Error> {
+        let watched: Vec<i64> = self
+            .watchpoints
+            .iter()
+            .map(|&address| self.intcode.memory.get(address))
+            .collect();
+        let instruction = self.intcode.current_instruction()?;
+        let pending = self.intcode.pending_inputs();
+        let mut undo = Undo {
+            instruction_pointer: self.intcode.instruction_pointer,
+            relative_base: self.intcode.relative_base,
+            memory_len: self.intcode.memory.len(),
+            written: None,
+            input: self.intcode.inputs.front().copied(),
+            outputs: self.outputs.len(),
+        };
+        let (state, written) = self.intcode.execute(instruction)?;
+        let stop = match state {
+            Some(RunState::NeedInput) => return Ok(Some(Stop::NeedInput)),
+            Some(RunState::Halted) => return Ok(Some(Stop::Halted)),
+            Some(RunState::Output(value)) => {
+                self.outputs.push(value);
+                None
+            }
+            None => None,
+        };
+        undo.written = written;
+        if self.intcode.pending_inputs() == pending {
+            undo.input = None;
+        }
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(undo);
+        let changed = self
+            .watchpoints
+            .iter()
+            .zip(watched)
+            .find_map(|(&address, old)| {
+                let new = self.intcode.memory.get(address);
+                (new != old).then_some(Stop::Watchpoint { address, old, new })
+            });
+        Ok(stop.or(changed))
+    }
+
+    /// Runs up to `count` instructions, stopping early at a watchpoint, for input or on halting.
+    /// Breakpoints are ignored.
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn step(&mut self, count: usize) -> Result<Stop, Error> {
+        for _ in 0..count {
+            if let Some(stop) = self.step_one()? {
+                return Ok(stop);
+            }
+        }
+        Ok(Stop::Stepped)
+    }
+
+    /// Runs until something stops it. The instruction it starts on always runs, so continuing
+    /// from a breakpoint doesn't just stop there again.
+    ///
+    /// # Errors
+    /// See [`IntCode::step`]
+    pub fn resume(&mut self) -> Result<Stop, Error> {
+        if let Some(stop) = self.step_one()? {
+            return Ok(stop);
+        }
+        loop {
+            if let Some(breakpoint) = self.breakpoint_hit() {
+                return Ok(Stop::Breakpoint(breakpoint));
+            }
+            if let Some(stop) = self.step_one()? {
+                return Ok(stop);
+            }
+        }
+    }
+
+    /// Undoes up to `count` steps, returning how many it could
+    pub fn back(&mut self, count: usize) -> usize {
+        let count = count.min(self.history.len());
+        for _ in 0..count {
+            let Some(undo) = self.history.pop_back() else {
+                break;
+            };
+            self.intcode.instruction_pointer = undo.instruction_pointer;
+            self.intcode.relative_base = undo.relative_base;
+            if let Some(written) = undo.written {
+                self.intcode.memory.set(written.address, written.old);
+            }
+            self.intcode.memory.truncate(undo.memory_len);
+            if let Some(input) = undo.input {
+                self.intcode.inputs.push_front(input);
+            }
+            self.outputs.truncate(undo.outputs);
+        }
+        count
+    }
+
+    /// The instruction pointer, relative base and next instruction
+    #[must_use]
+    pub fn registers(&self) -> String {
+        format!(
+            "ip {}  rb {}  inputs {}  | {}",
+            self.intcode.instruction_pointer(),
+            self.intcode.relative_base(),
+            self.intcode.pending_inputs(),
+            Line::decode_memory(&self.intcode.memory, self.intcode.instruction_pointer())
+        )
+    }
+
+    /// `len` cells from `start`, 8 to a row, stopping at the end of the address space
+    #[must_use]
+    pub fn dump(&self, start: usize, len: usize) -> String {
+        let end = start.saturating_add(len);
+        let mut dump = String::new();
+        for row in (start..end).step_by(8) {
+            let values: Vec<String> = (row..row.saturating_add(8).min(end))
+                .map(|address| format!("{:>6}", self.intcode.memory.get(address)))
+                .collect();
+            let _ = writeln!(dump, "{row:>6}: {}", values.join(" "));
+        }
+        dump
+    }
+
+    /// The next `count` instructions from the instruction pointer
+    #[must_use]
+    pub fn list(&self, count: usize) -> String {
+        let mut address = self.intcode.instruction_pointer();
+        let mut listing = String::new();
+        for n in 0..count {
+            let line = Line::decode_memory(&self.intcode.memory, address);
+            let marker = if n == 0 { "=>" } else { "  " };
+            let _ = writeln!(listing, "{marker} {address:>6}  {line}");
+            address += line.size();
+        }
+        listing
+    }
+
+    /// Does what the command says, returning what to show for it. [`Command::Quit`] is up to
+    /// the caller, and does nothing here.
+    ///
+    /// # Errors
+    /// If the machine hits an error while running
+    pub fn execute(&mut self, command: &Command) -> Result<String, Error> {
+        let toggled = |done: bool, yes: &str, no: &str| if done { yes } else { no }.to_string();
+        let output = match command {
+            Command::Step(count) => self.report(|debugger| debugger.step(*count))?,
+            Command::Continue => self.report(Self::resume)?,
+            Command::Back(count) => {
+                let undone = self.back(*count);
+                format!("back {undone}\n{}", self.registers())
+            }
+            Command::Break(breakpoint) => toggled(
+                self.add_breakpoint(*breakpoint),
+                "breakpoint set",
+                "already set",
+            ),
+            Command::Delete(breakpoint) => toggled(
+                self.remove_breakpoint(*breakpoint),
+                "breakpoint removed",
+                "no such breakpoint",
+            ),
+            Command::Watch(address) => toggled(self.watch(*address), "watching", "already watched"),
+            Command::Unwatch(address) => {
+                toggled(self.unwatch(*address), "not watching", "wasn't watched")
+            }
+            Command::Input(values) => {
+                self.intcode.push_inputs(values.iter().copied());
+                format!("{} inputs queued", self.intcode.pending_inputs())
+            }
+            Command::Registers => self.registers(),
+            Command::Dump { start, len } => self.dump(*start, *len).trim_end().to_string(),
+            Command::List(count) => self.list(*count).trim_end().to_string(),
+            Command::Help => HELP.trim().to_string(),
+            Command::Quit => String::new(),
+        };
+        Ok(output)
+    }
+
+    /// Runs `run`, then says why it stopped, any new outputs and where it's got to
+    fn report<F>(&mut self, run: F) -> Result<String, Error>
+    where
+        F: FnOnce(&mut Self) -> Result<Stop, Error>,
+    {
+        let outputs = self.outputs.len();
+        let stop = run(self)?;
+        let mut report = stop.to_string();
+        if let Some(new) = self.outputs.get(outputs..).filter(|new| !new.is_empty()) {
+            let _ = write!(report, "\noutput {new:?}");
+        }
+        let _ = write!(report, "\n{}", self.registers());
+        Ok(report)
+    }
+}
+
+const HELP: &str = "
+s [n]          step n instructions (default 1)
+c              continue to a breakpoint, watchpoint, input or halt
+r [n]          step back n instructions (default 1)
+b <addr|op>    set a breakpoint on an address or a mnemonic
+d <addr|op>    delete a breakpoint
+w <addr>       watch a memory cell
+u <addr>       stop watching a memory cell
+i <n>...       queue input values
+regs           show the instruction pointer and relative base
+x <addr> [n]   dump n memory cells (default 16)
+l [n]          list n instructions from the instruction pointer (default 8)
+h              this help
+q              quit
+";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    Back(usize),
+    Break(Breakpoint),
+    Delete(Breakpoint),
+    Watch(usize),
+    Unwatch(usize),
+    Input(Vec<i64>),
+    Registers,
+    Dump { start: usize, len: usize },
+    List(usize),
+    Help,
+    Quit,
+}
+
+/// See `h` for the syntax
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        let number = |n: usize, default: Option<usize>| {
+            args.get(n).map_or_else(
+                || default.ok_or_else(|| format!("{name} needs an argument")),
+                |arg| arg.parse().map_err(|_| format!("not a number: {arg:?}")),
+            )
+        };
+        let breakpoint = || args.first().ok_or("needs an address or mnemonic")?.parse();
+        let command = match name {
+            "s" | "step" => Self::Step(number(0, Some(1))?),
+            "c" | "continue" => Self::Continue,
+            "r" | "back" => Self::Back(number(0, Some(1))?),
+            "b" | "break" => Self::Break(breakpoint()?),
+            "d" | "delete" => Self::Delete(breakpoint()?),
+            "w" | "watch" => Self::Watch(number(0, None)?),
+            "u" | "unwatch" => Self::Unwatch(number(0, None)?),
+            "i" | "input" => Self::Input(
+                args.iter()
+                    .map(|arg| arg.parse().map_err(|_| format!("not a number: {arg:?}")))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "regs" => Self::Registers,
+            "x" | "dump" => Self::Dump {
+                start: number(0, None)?,
+                len: number(1, Some(16))?,
+            },
+            "l" | "list" => Self::List(number(0, Some(8))?),
+            "h" | "help" | "?" => Self::Help,
+            "q" | "quit" => Self::Quit,
+            _ => return Err(format!("unknown command {name:?}, try h")),
+        };
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assemble::assemble;
+
+    /// Counts down from the input, outputting each number
+    fn countdown() -> Debugger {
+        let memory = assemble(
+            "
+                in  [n]
+            loop:
+                out [n]
+                add  [n], -1, [n]
+                jnz [n], loop
+                hlt
+            n:  data 0
+            ",
+        )
+        .unwrap();
+        Debugger::new(IntCode::new(memory))
+    }
+
+    #[test]
+    fn test_step_and_back() {
+        let mut debugger = countdown();
+        assert_eq!(debugger.step(1), Ok(Stop::NeedInput));
+        debugger.intcode_mut().push_input(3);
+        assert_eq!(debugger.step(5), Ok(Stop::Stepped));
+        assert_eq!(debugger.outputs(), [3, 2]);
+        assert_eq!(debugger.intcode().instruction_pointer(), 4);
+        assert_eq!(debugger.back(1), 1);
+        assert_eq!(debugger.outputs(), [3]);
+        assert_eq!(debugger.intcode().instruction_pointer(), 2);
+        // all the way back to before the input was read
+        assert_eq!(debugger.back(10), 4);
+        assert_eq!(debugger.intcode().instruction_pointer(), 0);
+        assert_eq!(debugger.intcode().pending_inputs(), 1);
+        assert_eq!(debugger.resume(), Ok(Stop::Halted));
+        assert_eq!(debugger.outputs(), [3, 2, 1]);
+    }
+
+    #[test]
+    fn test_back_restores_everything() {
+        let memory = assemble(
+            "
+                arb 7
+                in  [rb+2000]
+                out [rb+2000]
+                hlt
+            ",
+        )
+        .unwrap();
+        let mut debugger = Debugger::new(IntCode::new(memory));
+        debugger.intcode_mut().push_inputs([5, 6]);
+        let start = debugger.intcode().clone();
+        assert_eq!(debugger.resume(), Ok(Stop::Halted));
+        assert_eq!(debugger.outputs(), [5]);
+        assert_eq!(debugger.intcode().memory.len(), 2008);
+        assert_eq!(debugger.back(3), 3);
+        assert_eq!(debugger.intcode(), &start);
+        assert!(debugger.outputs().is_empty());
+    }
+
+    #[test]
+    fn test_breakpoints_and_watchpoints() {
+        let mut debugger = countdown();
+        debugger.intcode_mut().push_input(3);
+        debugger.add_breakpoint(Breakpoint::Op(Op::Output));
+        assert_eq!(
+            debugger.resume(),
+            Ok(Stop::Breakpoint(Breakpoint::Op(Op::Output)))
+        );
+        assert_eq!(
+            debugger.resume(),
+            Ok(Stop::Breakpoint(Breakpoint::Op(Op::Output)))
+        );
+        assert_eq!(debugger.outputs(), [3]);
+        debugger.remove_breakpoint(Breakpoint::Op(Op::Output));
+        debugger.add_breakpoint(Breakpoint::Address(11));
+        debugger.watch(12);
+        assert_eq!(
+            debugger.resume(),
+            Ok(Stop::Watchpoint {
+                address: 12,
+                old: 2,
+                new: 1
+            })
+        );
+        debugger.unwatch(12);
+        assert_eq!(
+            debugger.resume(),
+            Ok(Stop::Breakpoint(Breakpoint::Address(11)))
+        );
+        assert_eq!(debugger.resume(), Ok(Stop::Halted));
+    }
+
+    #[test]
+    fn test_commands() {
+        let mut debugger = countdown();
+        let mut run = |command: &str| debugger.execute(&command.parse().unwrap()).unwrap();
+        assert_eq!(run("i 2"), "1 inputs queued");
+        assert_eq!(
+            run("s 2"),
+            "stepped\noutput [2]\nip 4  rb 0  inputs 0  | add  [12], -1, [12]"
+        );
+        assert_eq!(run("b jnz"), "breakpoint set");
+        assert_eq!(
+            run("c"),
+            "breakpoint jnz\nip 8  rb 0  inputs 0  | jnz  [12], 2"
+        );
+        assert_eq!(run("x 8 3"), "     8:   1005     12      2");
+        assert_eq!(
+            run("x 18446744073709551613 16"),
+            "18446744073709551613:      0      0"
+        );
+        assert_eq!(run("l 2"), "=>      8  jnz  [12], 2\n       11  hlt");
+        assert_eq!(
+            run("r"),
+            "back 1\nip 4  rb 0  inputs 0  | add  [12], -1, [12]"
+        );
+        assert_eq!(
+            "x".parse::<Command>(),
+            Err("x needs an argument".to_string())
+        );
+        assert!("b frob".parse::<Command>().is_err());
+        assert!("zap".parse::<Command>().is_err());
+    }
+}
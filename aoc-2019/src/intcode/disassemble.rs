@@ -0,0 +1,300 @@
+//! Turning Intcode memory back into something readable.
+//!
+//! Parameters are written the way the assembler reads them: `[5]` for position mode, `5` for
+//! immediate and `[rb+5]` for relative. Immediate jump targets get labels.
+//!
+//! It's a linear sweep, so data mixed in with code can throw it off until it lines back up, and
+//! self-modifying programs will look like whatever they were when disassembled.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{self, Display, Write};
+use std::hash::BuildHasher;
+
+use super::{Error, Instruction, IntCode, Memory, Mode, RunState};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Instruction {
+        address: usize,
+        instruction: Instruction,
+        params: Vec<i64>,
+    },
+    /// Anything that doesn't decode as an instruction
+    Data { address: usize, value: i64 },
+}
+
+impl Line {
+    /// Whatever's at `address`, treating memory past the end of `memory` as 0
+    #[must_use]
+    pub fn decode(memory: &[i64], address: usize) -> Self {
+        Self::decode_with(memory.len(), |address| memory[address], address)
+    }
+
+    /// [`Self::decode`] straight from a machine's memory, without copying it out first
+    #[must_use]
+    pub fn decode_memory(memory: &Memory, address: usize) -> Self {
+        Self::decode_with(memory.len(), |address| memory.get(address), address)
+    }
+
+    /// `get` is only called for addresses below `len`
+    fn decode_with(len: usize, get: impl Fn(usize) -> i64, address: usize) -> Self {
+        let value = if address < len { get(address) } else { 0 };
+        let decoded = Instruction::decode(address, value)
+            .ok()
+            .filter(|instruction| address + instruction.size() <= len);
+        let Some(instruction) = decoded else {
+            return Self::Data { address, value };
+        };
+        Self::Instruction {
+            address,
+            instruction,
+            params: (address + 1..address + instruction.size())
+                .map(get)
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    pub const fn address(&self) -> usize {
+        match self {
+            Self::Instruction { address, .. } | Self::Data { address, .. } => *address,
+        }
+    }
+
+    /// Number of memory cells it covers
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::Instruction { instruction, .. } => instruction.size(),
+            Self::Data { .. } => 1,
+        }
+    }
+
+    /// The assembly for this line, using labels for immediate jumps to any of `jump_targets`
+    fn text(&self, jump_targets: &BTreeSet<usize>) -> String {
+        match self {
+            Self::Instruction {
+                instruction,
+                params,
+                ..
+            } => {
+                let params: Vec<String> = params
+                    .iter()
+                    .enumerate()
+                    .map(|(n, &value)| format_param(*instruction, n, value, jump_targets))
+                    .collect();
+                format!("{:<4} {}", instruction.op.mnemonic(), params.join(", "))
+                    .trim_end()
+                    .to_string()
+            }
+            Self::Data { value, .. } => format!("data {value}"),
+        }
+    }
+}
+
+/// Just the assembly, no address and no labels
+impl Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text(&BTreeSet::new()))
+    }
+}
+
+fn label(address: usize) -> String {
+    format!("L{address}")
+}
+
+fn format_param(
+    instruction: Instruction,
+    n: usize,
+    value: i64,
+    jump_targets: &BTreeSet<usize>,
+) -> String {
+    match instruction.modes[n] {
+        Mode::Position => format!("[{value}]"),
+        Mode::Relative => format!("[rb{value:+}]"),
+        Mode::Immediate => match usize::try_from(value) {
+            Ok(target) if instruction.op.jumps() && n == 1 && jump_targets.contains(&target) => {
+                label(target)
+            }
+            _ => value.to_string(),
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    lines: Vec<Line>,
+    jump_targets: BTreeSet<usize>,
+}
+
+#[must_use]
+pub fn disassemble(memory: &[i64]) -> Disassembly {
+    let mut lines = vec![];
+    let mut jump_targets = BTreeSet::new();
+    let mut address = 0;
+    while address < memory.len() {
+        let line = Line::decode(memory, address);
+        if let Line::Instruction {
+            instruction,
+            params,
+            ..
+        } = &line
+        {
+            if instruction.op.jumps() && instruction.modes[1] == Mode::Immediate {
+                if let Ok(target) = usize::try_from(params[1]) {
+                    jump_targets.insert(target);
+                }
+            }
+        }
+        address += line.size();
+        lines.push(line);
+    }
+    // a jump into the middle of a line has nowhere to put its label, so it keeps the raw address
+    jump_targets.retain(|target| lines.binary_search_by_key(target, Line::address).is_ok());
+    Disassembly {
+        lines,
+        jump_targets,
+    }
+}
+
+impl Disassembly {
+    #[must_use]
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Addresses that an immediate jump goes to, and that a line starts at
+    #[must_use]
+    pub const fn jump_targets(&self) -> &BTreeSet<usize> {
+        &self.jump_targets
+    }
+
+    /// The listing, with how many times each instruction ran if there are `counts` (see
+    /// [`run_counting`])
+    #[must_use]
+    pub fn listing(&self, counts: Option<&HashMap<usize, usize>>) -> String {
+        let mut listing = String::new();
+        for line in &self.lines {
+            let address = line.address();
+            if self.jump_targets.contains(&address) {
+                let _ = writeln!(listing, "{}:", label(address));
+            }
+            let text = line.text(&self.jump_targets);
+            let _ = match counts {
+                Some(counts) => {
+                    let count = counts.get(&address).copied().unwrap_or(0);
+                    writeln!(listing, "{address:>6}  {text:<32} ; {count}")
+                }
+                None => writeln!(listing, "{address:>6}  {text}"),
+            };
+        }
+        listing
+    }
+}
+
+impl Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.listing(None))
+    }
+}
+
+/// [`IntCode::run`], but counting how many times the instruction at each address runs
+///
+/// # Errors
+/// See [`IntCode::step`]
+pub fn run_counting<S: BuildHasher>(
+    intcode: &mut IntCode,
+    counts: &mut HashMap<usize, usize, S>,
+) -> Result<RunState, Error> {
+    loop {
+        let address = intcode.instruction_pointer();
+        let state = intcode.step()?;
+        // waiting for input didn't actually run anything
+        if state != Some(RunState::NeedInput) {
+            *counts.entry(address).or_default() += 1;
+        }
+        if let Some(state) = state {
+            return Ok(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assemble::assemble;
+
+    #[test]
+    fn test_listing() {
+        let memory = [
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        let disassembly = disassemble(&memory);
+        assert_eq!(disassembly.jump_targets(), &BTreeSet::from([0]));
+        assert_eq!(
+            disassembly.to_string(),
+            "L0:
+     0  arb  1
+     2  out  [rb-1]
+     4  add  [100], 1, [100]
+     8  eq   [100], 16, [101]
+    12  jz   [101], L0
+    15  hlt
+"
+        );
+    }
+
+    #[test]
+    fn test_jump_into_instruction() {
+        // jumps to the second cell of the add
+        let memory = vec![1105, 1, 4, 1101, 2, 3, 20, 99];
+        let disassembly = disassemble(&memory);
+        assert!(disassembly.jump_targets().is_empty());
+        assert_eq!(
+            disassembly.to_string(),
+            "     0  jnz  1, 4
+     3  add  2, 3, [20]
+     7  hlt
+"
+        );
+        // and it still reassembles, without the address column
+        let listing = disassembly.to_string();
+        let listing: Vec<&str> = listing
+            .lines()
+            .map(|line| line.trim_start().trim_start_matches(char::is_numeric))
+            .collect();
+        assert_eq!(assemble(&listing.join("\n")), Ok(memory));
+    }
+
+    #[test]
+    fn test_data_and_counts() {
+        // 3 trips round the loop, then some trailing data that doesn't decode
+        let memory = vec![
+            1001, 20, 1, 20, 1007, 20, 3, 21, 1005, 21, 0, 99, 42, 123_456,
+        ];
+        let disassembly = disassemble(&memory);
+        assert_eq!(
+            disassembly.lines()[5],
+            Line::Data {
+                address: 13,
+                value: 123_456
+            }
+        );
+        let mut intcode = IntCode::new(memory);
+        let mut counts = HashMap::new();
+        assert_eq!(
+            run_counting(&mut intcode, &mut counts),
+            Ok(RunState::Halted)
+        );
+        assert_eq!(counts[&0], 3);
+        assert_eq!(counts[&11], 1);
+        let listing = disassembly.listing(Some(&counts));
+        let jump = listing
+            .lines()
+            .find(|line| line.starts_with("     8"))
+            .unwrap();
+        assert!(jump.starts_with("     8  jnz  [21], L0 "));
+        assert!(jump.ends_with(" ; 3"));
+        assert!(listing.contains("    12  data 42                          ; 0\n"));
+    }
+}
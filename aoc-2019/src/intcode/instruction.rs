@@ -0,0 +1,242 @@
+//! Decoding a memory cell into an opcode and its parameter modes.
+
+use super::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// The parameter is an address
+    Position,
+    /// The parameter is the value
+    Immediate,
+    /// The parameter is an address relative to the relative base
+    Relative,
+}
+
+impl Mode {
+    const fn from_digit(digit: i64) -> Option<Self> {
+        match digit {
+            0 => Some(Self::Position),
+            1 => Some(Self::Immediate),
+            2 => Some(Self::Relative),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Op {
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustBase,
+    Halt,
+}
+
+impl Op {
+    pub const ALL: [Self; 10] = [
+        Self::Add,
+        Self::Mul,
+        Self::Input,
+        Self::Output,
+        Self::JumpIfTrue,
+        Self::JumpIfFalse,
+        Self::LessThan,
+        Self::Equals,
+        Self::AdjustBase,
+        Self::Halt,
+    ];
+
+    #[must_use]
+    pub const fn from_code(code: i64) -> Option<Self> {
+        match code {
+            1 => Some(Self::Add),
+            2 => Some(Self::Mul),
+            3 => Some(Self::Input),
+            4 => Some(Self::Output),
+            5 => Some(Self::JumpIfTrue),
+            6 => Some(Self::JumpIfFalse),
+            7 => Some(Self::LessThan),
+            8 => Some(Self::Equals),
+            9 => Some(Self::AdjustBase),
+            99 => Some(Self::Halt),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn code(self) -> i64 {
+        match self {
+            Self::Add => 1,
+            Self::Mul => 2,
+            Self::Input => 3,
+            Self::Output => 4,
+            Self::JumpIfTrue => 5,
+            Self::JumpIfFalse => 6,
+            Self::LessThan => 7,
+            Self::Equals => 8,
+            Self::AdjustBase => 9,
+            Self::Halt => 99,
+        }
+    }
+
+    /// Short name used by the disassembler and assembler
+    #[must_use]
+    pub const fn mnemonic(self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Mul => "mul",
+            Self::Input => "in",
+            Self::Output => "out",
+            Self::JumpIfTrue => "jnz",
+            Self::JumpIfFalse => "jz",
+            Self::LessThan => "lt",
+            Self::Equals => "eq",
+            Self::AdjustBase => "arb",
+            Self::Halt => "hlt",
+        }
+    }
+
+    #[must_use]
+    pub fn from_mnemonic(mnemonic: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|op| op.mnemonic() == mnemonic)
+    }
+
+    /// Whether this can move the instruction pointer somewhere other than the next instruction
+    #[must_use]
+    pub const fn jumps(self) -> bool {
+        matches!(self, Self::JumpIfTrue | Self::JumpIfFalse)
+    }
+
+    #[must_use]
+    pub const fn param_count(self) -> usize {
+        match self {
+            Self::Add | Self::Mul | Self::LessThan | Self::Equals => 3,
+            Self::JumpIfTrue | Self::JumpIfFalse => 2,
+            Self::Input | Self::Output | Self::AdjustBase => 1,
+            Self::Halt => 0,
+        }
+    }
+
+    /// Whether the last parameter is an address to write to
+    #[must_use]
+    pub const fn writes(self) -> bool {
+        matches!(
+            self,
+            Self::Add | Self::Mul | Self::Input | Self::LessThan | Self::Equals
+        )
+    }
+}
+
+/// An opcode plus the modes for each of its parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    pub op: Op,
+    pub modes: [Mode; 3],
+}
+
+impl Instruction {
+    /// `address` is only for the error.
+    ///
+    /// # Errors
+    /// If the opcode isn't one of the known ones, or a parameter mode digit isn't 0, 1 or 2 (or
+    /// is 1 for a parameter that gets written to). Mode digits past the op's parameters have to
+    /// be 0.
+    pub fn decode(address: usize, value: i64) -> Result<Self, Error> {
+        let op = Op::from_code(value.rem_euclid(100))
+            .filter(|_| value >= 0)
+            .ok_or(Error::UnknownOpcode { address, value })?;
+        let mut modes = [Mode::Position; 3];
+        let mut digits = value / 100;
+        for (i, mode) in modes.iter_mut().enumerate() {
+            let digit = digits % 10;
+            digits /= 10;
+            *mode = Mode::from_digit(digit)
+                .filter(|&mode| i < op.param_count() || mode == Mode::Position)
+                .ok_or(Error::InvalidMode { address, value })?;
+            if op.writes() && i + 1 == op.param_count() && *mode == Mode::Immediate {
+                return Err(Error::ImmediateWrite { address });
+            }
+        }
+        if digits != 0 {
+            return Err(Error::InvalidMode { address, value });
+        }
+        Ok(Self { op, modes })
+    }
+
+    /// Back into a memory value, the inverse of [`Self::decode`]
+    #[must_use]
+    pub fn encode(&self) -> i64 {
+        let modes = self.modes.iter().rev().fold(0, |acc, mode| {
+            acc * 10
+                + match mode {
+                    Mode::Position => 0,
+                    Mode::Immediate => 1,
+                    Mode::Relative => 2,
+                }
+        });
+        modes * 100 + self.op.code()
+    }
+
+    /// Number of memory cells taken up, opcode included
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        1 + self.op.param_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let instruction = Instruction::decode(0, 1002).unwrap();
+        assert_eq!(instruction.op, Op::Mul);
+        assert_eq!(
+            instruction.modes,
+            [Mode::Position, Mode::Immediate, Mode::Position]
+        );
+        assert_eq!(instruction.encode(), 1002);
+        assert_eq!(
+            Instruction::decode(0, 204).unwrap().modes[0],
+            Mode::Relative
+        );
+        assert_eq!(Instruction::decode(0, 99).unwrap().size(), 1);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            Instruction::decode(3, 42),
+            Err(Error::UnknownOpcode {
+                address: 3,
+                value: 42
+            })
+        );
+        assert_eq!(
+            Instruction::decode(0, 301),
+            Err(Error::InvalidMode {
+                address: 0,
+                value: 301
+            })
+        );
+        // halt has no parameters to have modes
+        assert_eq!(
+            Instruction::decode(0, 199),
+            Err(Error::InvalidMode {
+                address: 0,
+                value: 199
+            })
+        );
+        assert_eq!(
+            Instruction::decode(7, 11101),
+            Err(Error::ImmediateWrite { address: 7 })
+        );
+        assert!(Instruction::decode(0, -1).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Intcode memory: as big as it needs to be, and zero everywhere nothing's been written.
+//!
+//! It's stored in fixed size chunks behind [`Rc`]s, so cloning a machine only copies pointers,
+//! and a chunk is only copied the first time one of the clones writes to it. The program and
+//! anything written near it are contiguous, writes a long way past that get chunks of their own
+//! so a program poking at `[1000000000000000]` doesn't allocate everything in between.
+
+use std::collections::BTreeMap;
+use std::ops::{Index, IndexMut};
+use std::rc::Rc;
+
+const CHUNK: usize = 512;
+
+/// Writes more than this many chunks past the contiguous memory go in `far` instead
+const MAX_GAP: usize = 64;
+
+type Chunk = Rc<[i64; CHUNK]>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Memory {
+    chunks: Vec<Chunk>,
+    len: usize,
+    /// Chunks too far past `chunks` to fill in the gap to, by chunk number. Only chunks with
+    /// something other than zero in them are kept.
+    far: BTreeMap<usize, Chunk>,
+}
+
+impl Memory {
+    #[must_use]
+    pub fn new(program: &[i64]) -> Self {
+        let chunks = program
+            .chunks(CHUNK)
+            .map(|values| {
+                let mut chunk = [0; CHUNK];
+                chunk[..values.len()].copy_from_slice(values);
+                Rc::new(chunk)
+            })
+            .collect();
+        Self {
+            chunks,
+            len: program.len(),
+            far: BTreeMap::new(),
+        }
+    }
+
+    /// 0 past the end rather than panicking
+    #[must_use]
+    pub fn get(&self, address: usize) -> i64 {
+        self[address]
+    }
+
+    /// Grows the memory if `address` is past the end
+    pub fn set(&mut self, address: usize, value: i64) {
+        self[address] = value;
+        let number = address / CHUNK;
+        if value == 0
+            && self
+                .far
+                .get(&number)
+                .is_some_and(|chunk| chunk.iter().all(|&cell| cell == 0))
+        {
+            self.far.remove(&number);
+        }
+    }
+
+    /// Makes sure chunk number `number` is contiguous, pulling in any far chunks on the way. New
+    /// chunks all start out as the same chunk of zeros.
+    fn grow(&mut self, number: usize) {
+        if number < self.chunks.len() {
+            return;
+        }
+        let zeros = Rc::new([0; CHUNK]);
+        while self.chunks.len() <= number {
+            let next = self.chunks.len();
+            match self.far.remove(&next) {
+                Some(chunk) => {
+                    self.chunks.push(chunk);
+                    self.len = (next + 1) * CHUNK;
+                }
+                None => self.chunks.push(Rc::clone(&zeros)),
+            }
+        }
+    }
+
+    /// Forgets everything from `len` on, for undoing a write that grew the memory. Anything past
+    /// `len` has to be back to zero already, apart from chunks that were pulled in from far away,
+    /// which go back there.
+    pub(super) fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.len = len;
+        let kept = len.div_ceil(CHUNK);
+        for (number, chunk) in self.chunks.drain(kept..).enumerate() {
+            if chunk.iter().any(|&cell| cell != 0) {
+                self.far.insert(kept + number, chunk);
+            }
+        }
+    }
+
+    /// The contiguous part of memory: the program plus anything written after it. Far away
+    /// writes aren't included.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<i64> {
+        self.iter().collect()
+    }
+
+    /// See [`Self::to_vec`]
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| chunk.iter())
+            .copied()
+            .take(self.len)
+    }
+
+    /// Length of the contiguous part, see [`Self::to_vec`]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many chunks are still shared with `other`, rather than copied
+    #[must_use]
+    pub fn shared_chunks(&self, other: &Self) -> usize {
+        let far = self
+            .far
+            .iter()
+            .filter(|(number, chunk)| other.far.get(number).is_some_and(|o| Rc::ptr_eq(chunk, o)))
+            .count();
+        self.chunks
+            .iter()
+            .zip(&other.chunks)
+            .filter(|(a, b)| Rc::ptr_eq(a, b))
+            .count()
+            + far
+    }
+}
+
+impl Index<usize> for Memory {
+    type Output = i64;
+
+    fn index(&self, address: usize) -> &Self::Output {
+        let number = address / CHUNK;
+        // contiguous chunks are all zero past `len`
+        self.chunks
+            .get(number)
+            .or_else(|| self.far.get(&number))
+            .map_or(&0, |chunk| &chunk[address % CHUNK])
+    }
+}
+
+impl IndexMut<usize> for Memory {
+    fn index_mut(&mut self, address: usize) -> &mut Self::Output {
+        let number = address / CHUNK;
+        let chunk = if number < self.chunks.len() + MAX_GAP {
+            self.grow(number);
+            self.len = self.len.max(address + 1);
+            &mut self.chunks[number]
+        } else {
+            self.far
+                .entry(number)
+                .or_insert_with(|| Rc::new([0; CHUNK]))
+        };
+        &mut Rc::make_mut(chunk)[address % CHUNK]
+    }
+}
+
+impl From<Vec<i64>> for Memory {
+    fn from(program: Vec<i64>) -> Self {
+        Self::new(&program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grow() {
+        let mut memory = Memory::new(&[1, 2, 3]);
+        assert_eq!(memory[10_000], 0);
+        assert_eq!(memory.len(), 3);
+        memory[2000] = 5;
+        assert_eq!(memory.len(), 2001);
+        assert_eq!(memory.get(2000), 5);
+        assert_eq!(memory.get(1999), 0);
+        assert_eq!(memory.to_vec()[..4], [1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_copy_on_write() {
+        let mut memory = Memory::from((0..2000).collect::<Vec<_>>());
+        let copy = memory.clone();
+        assert_eq!(memory.shared_chunks(&copy), 4);
+        memory[600] = -1;
+        assert_eq!(memory.shared_chunks(&copy), 3);
+        assert_eq!(copy[600], 600);
+        assert_eq!(memory[600], -1);
+        assert_ne!(memory, copy);
+    }
+
+    #[test]
+    fn test_far_writes() {
+        let mut memory = Memory::new(&[1, 2, 3]);
+        memory[1_000_000_000_000_000] = 7;
+        memory[usize::MAX] = 8;
+        assert_eq!(memory.len(), 3);
+        assert_eq!(memory.get(1_000_000_000_000_000), 7);
+        assert_eq!(memory.get(1_000_000_000_000_001), 0);
+        assert_eq!(memory.get(usize::MAX), 8);
+        let copy = memory.clone();
+        memory.set(usize::MAX, 0);
+        assert_eq!(memory.shared_chunks(&copy), 2);
+        // a far chunk becomes contiguous once the writes catch up with it
+        let mut memory = Memory::new(&[]);
+        memory[100 * CHUNK] = 5;
+        memory[40 * CHUNK] = 4;
+        assert_eq!(memory.len(), 40 * CHUNK + 1);
+        memory[101 * CHUNK + 1] = 1;
+        assert_eq!(memory.len(), 101 * CHUNK + 2);
+        assert_eq!(memory.to_vec()[100 * CHUNK], 5);
+        // undoing the write that pulled it in sends it back
+        memory.set(101 * CHUNK + 1, 0);
+        memory.truncate(40 * CHUNK + 1);
+        assert_eq!(memory.get(100 * CHUNK), 5);
+        assert_eq!(memory.len(), 40 * CHUNK + 1);
+    }
+}
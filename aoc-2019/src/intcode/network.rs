@@ -0,0 +1,278 @@
+//! A bunch of Intcode machines sending each other `(address, x, y)` packets, run round robin on
+//! one thread.
+//!
+//! Each machine boots with its address as its first input, gets `-1` whenever it asks for input
+//! and has none, and outputs packets as three values in a row. Packets for an address with no
+//! machine (like 255) go to the [`Nat`], which also gets a say whenever the whole network goes
+//! idle.
+
+use std::fmt::{self, Display};
+
+use super::{Error, IntCode, RunState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Packet {
+    pub dest: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// What the NAT wants to happen next
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NatAction<T> {
+    Continue,
+    Send(Packet),
+    /// Stop the network, and have [`Network::run`] return this
+    Stop(T),
+}
+
+/// Whatever's listening on the addresses that don't have a machine
+pub trait Nat {
+    type Output;
+
+    /// A packet sent to an address with no machine behind it
+    fn receive(&mut self, packet: Packet) -> NatAction<Self::Output>;
+
+    /// Every queue is empty and nothing was sent for a whole round. Returning
+    /// [`NatAction::Continue`] here means nothing will ever happen again, so the network stops
+    /// with [`NetworkError::Stalled`].
+    fn idle(&mut self) -> NatAction<Self::Output>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    Machine {
+        address: usize,
+        error: Error,
+    },
+    /// Idle, and the NAT didn't do anything about it
+    Stalled,
+    /// Every machine halted before the NAT stopped the network
+    AllHalted,
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Machine { address, error } => write!(f, "machine {address}: {error}"),
+            Self::Stalled => write!(f, "network idle and the NAT did nothing"),
+            Self::AllHalted => write!(f, "every machine halted"),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+#[derive(Debug, Clone)]
+pub struct Network {
+    machines: Vec<IntCode>,
+    /// Outputs so far towards each machine's next packet
+    partial: Vec<Vec<i64>>,
+    halted: Vec<bool>,
+    idle_rounds: usize,
+}
+
+impl Network {
+    /// `size` copies of `program`, booted with addresses `0..size`
+    #[must_use]
+    pub fn new(program: &IntCode, size: usize) -> Self {
+        let machines = (0..size)
+            .map(|address| {
+                let mut machine = program.clone();
+                machine.push_input(i64::try_from(address).unwrap_or(i64::MAX));
+                machine
+            })
+            .collect();
+        Self {
+            machines,
+            partial: vec![vec![]; size],
+            halted: vec![false; size],
+            idle_rounds: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn machines(&self) -> &[IntCode] {
+        &self.machines
+    }
+
+    /// How many times the network has gone idle so far
+    #[must_use]
+    pub const fn idle_rounds(&self) -> usize {
+        self.idle_rounds
+    }
+
+    /// Queues the packet for its machine, returns it back if there's no machine at that address
+    fn route(&mut self, packet: Packet) -> Option<Packet> {
+        let machine = usize::try_from(packet.dest)
+            .ok()
+            .and_then(|address| self.machines.get_mut(address));
+        let Some(machine) = machine else {
+            return Some(packet);
+        };
+        machine.push_inputs([packet.x, packet.y]);
+        None
+    }
+
+    /// Gives every machine one turn: it runs until it's used up its input (getting a -1 if it
+    /// had none) or halts. Returns whether anything happened, and stops early if the NAT says
+    /// so.
+    fn round<N: Nat>(&mut self, nat: &mut N) -> Result<Result<bool, N::Output>, NetworkError> {
+        let mut active = false;
+        for address in 0..self.machines.len() {
+            if self.halted[address] {
+                continue;
+            }
+            let machine = &mut self.machines[address];
+            if machine.pending_inputs() == 0 {
+                machine.push_input(-1);
+            } else {
+                active = true;
+            }
+            loop {
+                let state = self.machines[address]
+                    .run()
+                    .map_err(|error| NetworkError::Machine { address, error })?;
+                match state {
+                    RunState::Output(value) => {
+                        let partial = &mut self.partial[address];
+                        partial.push(value);
+                        if let [dest, x, y] = partial[..] {
+                            partial.clear();
+                            active = true;
+                            if let Some(packet) = self.route(Packet { dest, x, y }) {
+                                match nat.receive(packet) {
+                                    NatAction::Continue => {}
+                                    NatAction::Send(packet) => self.send_from_nat(packet),
+                                    NatAction::Stop(output) => return Ok(Err(output)),
+                                }
+                            }
+                        }
+                    }
+                    RunState::NeedInput => break,
+                    RunState::Halted => {
+                        self.halted[address] = true;
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(Ok(active))
+    }
+
+    /// Anything the NAT sends to a non-machine address just disappears
+    fn send_from_nat(&mut self, packet: Packet) {
+        let _ = self.route(packet);
+    }
+
+    /// Runs until the NAT stops it
+    ///
+    /// # Errors
+    /// If a machine hits an error, or the network can't make any more progress
+    pub fn run<N: Nat>(&mut self, nat: &mut N) -> Result<N::Output, NetworkError> {
+        loop {
+            let active = match self.round(nat)? {
+                Ok(active) => active,
+                Err(output) => return Ok(output),
+            };
+            if self.halted.iter().all(|&halted| halted) {
+                return Err(NetworkError::AllHalted);
+            }
+            if !active {
+                self.idle_rounds += 1;
+                match nat.idle() {
+                    NatAction::Continue => return Err(NetworkError::Stalled),
+                    NatAction::Send(packet) => self.send_from_nat(packet),
+                    NatAction::Stop(output) => return Ok(output),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reads packets forever, and for each sends `(own address + 1, x + 1, y)`
+    const RELAY: &str = "3,100,3,101,1008,101,-1,103,1005,103,2,3,102,1001,100,1,104,\
+                         4,104,1001,101,1,101,4,101,4,102,1105,1,2";
+
+    /// Remembers the last packet it got, and sends it back to 0 whenever the network is idle
+    struct Loopback {
+        last: Option<Packet>,
+        stop_at: i64,
+    }
+
+    impl Nat for Loopback {
+        type Output = Packet;
+
+        fn receive(&mut self, packet: Packet) -> NatAction<Packet> {
+            if packet.x >= self.stop_at {
+                return NatAction::Stop(packet);
+            }
+            self.last = Some(packet);
+            NatAction::Continue
+        }
+
+        fn idle(&mut self) -> NatAction<Packet> {
+            let last = self.last.unwrap_or(Packet {
+                dest: 0,
+                x: 0,
+                y: 42,
+            });
+            NatAction::Send(Packet { dest: 0, ..last })
+        }
+    }
+
+    #[test]
+    fn test_relay_through_nat() {
+        let program: IntCode = RELAY.parse().unwrap();
+        let mut network = Network::new(&program, 3);
+        let mut nat = Loopback {
+            last: None,
+            stop_at: 9,
+        };
+        // 0 -> 1 -> 2 -> NAT three times round, starting from nothing
+        let packet = network.run(&mut nat).unwrap();
+        assert_eq!(
+            packet,
+            Packet {
+                dest: 3,
+                x: 9,
+                y: 42
+            }
+        );
+        assert_eq!(network.idle_rounds(), 3);
+    }
+
+    struct DoNothing;
+
+    impl Nat for DoNothing {
+        type Output = ();
+
+        fn receive(&mut self, _packet: Packet) -> NatAction<()> {
+            NatAction::Continue
+        }
+
+        fn idle(&mut self) -> NatAction<()> {
+            NatAction::Continue
+        }
+    }
+
+    #[test]
+    fn test_stalled_and_halted() {
+        let program: IntCode = RELAY.parse().unwrap();
+        let mut network = Network::new(&program, 2);
+        assert_eq!(network.run(&mut DoNothing), Err(NetworkError::Stalled));
+
+        let mut network = Network::new(&"3,0,99".parse().unwrap(), 2);
+        assert_eq!(network.run(&mut DoNothing), Err(NetworkError::AllHalted));
+
+        let mut network = Network::new(&"3,0,42".parse().unwrap(), 2);
+        assert!(matches!(
+            network.run(&mut DoNothing),
+            Err(NetworkError::Machine { address: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,376 @@
+//! Robots driven by Intcode programs: hull painters, maze droids and cameras.
+//!
+//! Positions are signed `(x, y)` with y going down the screen, so [`Heading::Up`] is `(0, -1)`,
+//! and the robot starts at `(0, 0)` facing up.
+
+use std::collections::HashMap;
+
+use aoc_helpers::TwoDArray;
+
+use super::{Error, IntCode, RunState};
+
+pub type Position = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Heading {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Heading {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    #[must_use]
+    pub const fn turn_left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Right => Self::Up,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+        }
+    }
+
+    #[must_use]
+    pub const fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    #[must_use]
+    pub const fn reverse(self) -> Self {
+        self.turn_left().turn_left()
+    }
+
+    #[must_use]
+    pub const fn step(self) -> Position {
+        match self {
+            Self::Up => (0, -1),
+            Self::Right => (1, 0),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+
+    /// The position one step this way from `position`
+    #[must_use]
+    pub const fn move_from(self, position: Position) -> Position {
+        let (dx, dy) = self.step();
+        (position.0 + dx, position.1 + dy)
+    }
+
+    /// How the maze droids want directions: north 1, south 2, west 3, east 4
+    #[must_use]
+    pub const fn movement_command(self) -> i64 {
+        match self {
+            Self::Up => 1,
+            Self::Down => 2,
+            Self::Left => 3,
+            Self::Right => 4,
+        }
+    }
+}
+
+/// An unbounded grid, only storing the cells that have been set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas<T> {
+    cells: HashMap<Position, T>,
+}
+
+impl<T> Default for Canvas<T> {
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Canvas<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.cells.get(&position)
+    }
+
+    /// Returns what was there before
+    pub fn set(&mut self, position: Position, value: T) -> Option<T> {
+        self.cells.insert(position, value)
+    }
+
+    /// Number of cells that have been set
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells
+            .iter()
+            .map(|(&position, value)| (position, value))
+    }
+
+    /// Top left and bottom right corners (inclusive) of everything that's been set
+    #[must_use]
+    pub fn bounds(&self) -> Option<(Position, Position)> {
+        let xs = self.cells.keys().map(|&(x, _)| x);
+        let ys = self.cells.keys().map(|&(_, y)| y);
+        Some((
+            (xs.clone().min()?, ys.clone().min()?),
+            (xs.max()?, ys.max()?),
+        ))
+    }
+
+    /// One line per row within [`Self::bounds`], using `show` for each cell
+    pub fn render<F: Fn(Option<&T>) -> char>(&self, show: F) -> String {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return String::new();
+        };
+        (min_y..=max_y)
+            .map(|y| {
+                let mut row: String = (min_x..=max_x).map(|x| show(self.get((x, y)))).collect();
+                row.push('\n');
+                row
+            })
+            .collect()
+    }
+}
+
+impl<T: Clone> Canvas<T> {
+    /// Everything within [`Self::bounds`], with `default` where nothing's been set. `(0, 0)` in
+    /// the array is the top left corner of the bounds.
+    #[must_use]
+    pub fn to_array(&self, default: &T) -> TwoDArray<T> {
+        let Some(((min_x, min_y), (max_x, max_y))) = self.bounds() else {
+            return TwoDArray { elems: vec![] };
+        };
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| self.get((x, y)).unwrap_or(default).clone())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Robot {
+    pub intcode: IntCode,
+    pub position: Position,
+    pub heading: Heading,
+}
+
+impl Robot {
+    #[must_use]
+    pub const fn new(intcode: IntCode) -> Self {
+        Self {
+            intcode,
+            position: (0, 0),
+            heading: Heading::Up,
+        }
+    }
+
+    /// The next output, or `None` if the program halted instead
+    fn output(&mut self) -> Result<Option<i64>, Error> {
+        match self.intcode.run()? {
+            RunState::Output(value) => Ok(Some(value)),
+            RunState::Halted => Ok(None),
+            RunState::NeedInput => Err(Error::NoInput {
+                address: self.intcode.instruction_pointer(),
+            }),
+        }
+    }
+
+    /// Hull painting: the program gets the colour under the robot (0 if unpainted), outputs a
+    /// colour to paint and then 0 to turn left or 1 to turn right, and the robot moves forward
+    /// one. Runs until the program halts.
+    ///
+    /// # Errors
+    /// See [`IntCode::step`], and [`Error::NoInput`] if it asks for input mid-move
+    pub fn paint(&mut self, canvas: &mut Canvas<i64>) -> Result<(), Error> {
+        loop {
+            let colour = canvas.get(self.position).copied().unwrap_or(0);
+            self.intcode.push_input(colour);
+            let Some(paint) = self.output()? else {
+                return Ok(());
+            };
+            canvas.set(self.position, paint);
+            let Some(turn) = self.output()? else {
+                return Ok(());
+            };
+            self.heading = if turn == 0 {
+                self.heading.turn_left()
+            } else {
+                self.heading.turn_right()
+            };
+            self.position = self.heading.move_from(self.position);
+        }
+    }
+
+    /// Maze droids: sends the movement command for `heading` and returns the status the program
+    /// answers with. Anything other than 0 (a wall) moves the robot.
+    ///
+    /// # Errors
+    /// See [`IntCode::step`], [`Error::NoInput`] if it asks for more input before answering and
+    /// [`Error::UnexpectedHalt`] if it halts without answering
+    pub fn try_move(&mut self, heading: Heading) -> Result<i64, Error> {
+        self.heading = heading;
+        self.intcode.push_input(heading.movement_command());
+        let status = self.output()?.ok_or_else(|| Error::UnexpectedHalt {
+            address: self.intcode.instruction_pointer(),
+        })?;
+        if status != 0 {
+            self.position = heading.move_from(self.position);
+        }
+        Ok(status)
+    }
+}
+
+/// Runs the program until it halts or wants input, and reads what it output as an ASCII picture,
+/// one row per line. Blank lines are dropped.
+///
+/// Outputs that aren't ASCII (like day 17's dust count) come back separately, in order.
+///
+/// # Errors
+/// See [`IntCode::step`]
+pub fn camera_view(intcode: &mut IntCode) -> Result<(TwoDArray<char>, Vec<i64>), Error> {
+    let mut text = String::new();
+    let mut values = vec![];
+    while let RunState::Output(value) = intcode.run()? {
+        match u8::try_from(value) {
+            Ok(c) if c.is_ascii() => text.push(char::from(c)),
+            _ => values.push(value),
+        }
+    }
+    let view = text
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().collect())
+        .collect();
+    Ok((view, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::assemble::assemble;
+
+    fn program(source: &str) -> IntCode {
+        IntCode::new(assemble(source).unwrap())
+    }
+
+    #[test]
+    fn test_paint() {
+        // the day 11 example, ignoring what's underneath
+        let moves = [(1, 0), (0, 0), (1, 0), (1, 0), (0, 1), (1, 0), (1, 0)];
+        let source: String = moves
+            .iter()
+            .map(|(paint, turn)| format!("in [t]\nout {paint}\nout {turn}\n"))
+            .chain(["hlt\nt: data 0".to_string()])
+            .collect();
+        let mut robot = Robot::new(program(&source));
+        let mut canvas = Canvas::new();
+        robot.paint(&mut canvas).unwrap();
+        assert_eq!(canvas.len(), 6);
+        assert_eq!(robot.position, (0, -1));
+        assert_eq!(robot.heading, Heading::Left);
+        let show = |colour: Option<&i64>| if colour == Some(&1) { '#' } else { '.' };
+        assert_eq!(canvas.render(show), "..#\n..#\n##.\n");
+        let array = canvas.to_array(&0);
+        assert_eq!(array[(2, 0)], 1);
+        assert_eq!(array[(2, 2)], 0);
+    }
+
+    #[test]
+    fn test_paint_reads_canvas() {
+        // flips the colour underneath and turns right, going round a 2x2 square twice
+        let mut robot = Robot::new(program(
+            "
+            loop:
+                in   [t]
+                eq   [t], 0, [t]
+                out  [t]
+                out  1
+                add  [n], -1, [n]
+                jnz  [n], loop
+                hlt
+            t:  data 0
+            n:  data 8
+            ",
+        ));
+        let mut canvas = Canvas::new();
+        robot.paint(&mut canvas).unwrap();
+        assert_eq!(canvas.len(), 4);
+        assert!(canvas.cells().all(|(_, &colour)| colour == 0));
+        assert_eq!((robot.position, robot.heading), ((0, 0), Heading::Up));
+        assert_eq!(canvas.bounds(), Some(((0, 0), (1, 1))));
+    }
+
+    #[test]
+    fn test_try_move() {
+        // a wall to the west, open everywhere else
+        let mut robot = Robot::new(program(
+            "
+            loop:
+                in   [d]
+                eq   [d], 3, [t]
+                jnz  [t], wall
+                out  1
+                jz   0, loop
+            wall:
+                out  0
+                jz   0, loop
+            d:  data 0
+            t:  data 0
+            ",
+        ));
+        assert_eq!(robot.try_move(Heading::Up), Ok(1));
+        assert_eq!(robot.try_move(Heading::Left), Ok(0));
+        assert_eq!(robot.position, (0, -1));
+        assert_eq!(robot.try_move(Heading::Right), Ok(1));
+        assert_eq!(robot.position, (1, -1));
+        assert_eq!(Heading::Right.reverse(), Heading::Left);
+    }
+
+    #[test]
+    fn test_try_move_halts() {
+        let mut robot = Robot::new(program("in [0]\nhlt"));
+        assert_eq!(
+            robot.try_move(Heading::Up),
+            Err(Error::UnexpectedHalt { address: 2 })
+        );
+        let mut robot = Robot::new(program("in [0]\nin [0]"));
+        assert_eq!(
+            robot.try_move(Heading::Up),
+            Err(Error::NoInput { address: 2 })
+        );
+    }
+
+    #[test]
+    fn test_camera_view() {
+        let source: String = "#.^\n..#\n\n"
+            .bytes()
+            .map(|c| format!("out {c}\n"))
+            .chain(["out 1234\nhlt".to_string()])
+            .collect();
+        let (view, values) = camera_view(&mut program(&source)).unwrap();
+        assert_eq!(values, [1234]);
+        assert_eq!(view.y_len(), 2);
+        assert_eq!(view[(2, 0)], '^');
+        assert_eq!(view[1], ['.', '.', '#']);
+    }
+}
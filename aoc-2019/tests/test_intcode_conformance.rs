@@ -0,0 +1,327 @@
+use aoc_2019::intcode::assemble::assemble;
+use aoc_2019::intcode::cached::CachedIntCode;
+use aoc_2019::intcode::{Error, IntCode, Op};
+
+struct Case {
+    name: &'static str,
+    source: &'static str,
+    inputs: &'static [i64],
+    expected: Result<&'static [i64], Error>,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "echo",
+        source: "
+            in  [x]
+            out [x]
+            hlt
+        x:  data 0
+        ",
+        inputs: &[17],
+        expected: Ok(&[17]),
+    },
+    Case {
+        name: "sum until zero",
+        source: "
+        loop:   in   [n]
+                jz   [n], done
+                add  [sum], [n], [sum]
+                jz   0, loop
+        done:   out  [sum]
+                hlt
+        n:      data 0
+        sum:    data 0
+        ",
+        inputs: &[3, 4, 5, 0],
+        expected: Ok(&[12]),
+    },
+    Case {
+        name: "jumps taken and not taken",
+        source: "
+            jnz 0, fail
+            jz  1, fail
+            jnz 7, ok
+        fail:
+            out 0
+            hlt
+        ok: jz  0, ok2
+            out 0
+        ok2:
+            out 1
+            hlt
+        ",
+        inputs: &[],
+        expected: Ok(&[1]),
+    },
+    Case {
+        name: "jump target in position mode",
+        source: "
+            jnz 1, [target]
+            out 1
+            hlt
+        there:
+            out 2
+            hlt
+        target: data there
+        ",
+        inputs: &[],
+        expected: Ok(&[2]),
+    },
+    Case {
+        name: "jump target in relative mode",
+        source: "
+            arb table
+            jz  0, [rb+1]
+            out 1
+            hlt
+        there:
+            out 2
+            hlt
+        table: data 0, there
+        ",
+        inputs: &[],
+        expected: Ok(&[2]),
+    },
+    Case {
+        name: "negative numbers",
+        source: "
+            add -5, -7, [x]
+            out [x]
+            mul -3, 4, [x]
+            out [x]
+            lt  -1, 0, [x]
+            out [x]
+            eq  -2, -2, [x]
+            out [x]
+            out -9
+            hlt
+        x:  data 0
+        ",
+        inputs: &[],
+        expected: Ok(&[-12, -12, 1, 1, -9]),
+    },
+    Case {
+        name: "big numbers",
+        source: "
+            mul 34915192, 34915192, [x]
+            out [x]
+            out 1125899906842624
+            hlt
+        x:  data 0
+        ",
+        inputs: &[],
+        expected: Ok(&[1_219_070_632_396_864, 1_125_899_906_842_624]),
+    },
+    Case {
+        name: "relative base moves both ways",
+        source: "
+            arb 100
+            in  [rb+5]
+            arb -3
+            out [rb+8]
+            arb -97
+            out [rb+0]
+            hlt
+        ",
+        inputs: &[8],
+        expected: Ok(&[8, 109]),
+    },
+    Case {
+        name: "memory past the program is zero and grows on write",
+        source: "
+            out [5000]
+            add 20, 22, [1000]
+            out [1000]
+            hlt
+        ",
+        inputs: &[],
+        expected: Ok(&[0, 42]),
+    },
+    Case {
+        name: "writes to huge addresses",
+        source: "
+            add 20, 22, [1000000000000000]
+            out [1000000000000000]
+            out [999999999999999]
+            in  [9223372036854775807]
+            out [9223372036854775807]
+            hlt
+        ",
+        inputs: &[-3],
+        expected: Ok(&[42, 0, -3]),
+    },
+    Case {
+        name: "self-modifying operand",
+        source: "
+            add 0, 5, [patch+1]
+        patch:
+            out 0
+            hlt
+        ",
+        inputs: &[],
+        expected: Ok(&[5]),
+    },
+    Case {
+        name: "self-modifying opcode",
+        source: "
+            add 0, 99, [patch]
+        patch:
+            out 1
+            out 2
+            hlt
+        ",
+        inputs: &[],
+        expected: Ok(&[]),
+    },
+    Case {
+        name: "unknown opcode",
+        source: "
+            out 1
+            data 42
+        ",
+        inputs: &[],
+        expected: Err(Error::UnknownOpcode {
+            address: 2,
+            value: 42,
+        }),
+    },
+    Case {
+        name: "invalid mode",
+        source: "data 301",
+        inputs: &[],
+        expected: Err(Error::InvalidMode {
+            address: 0,
+            value: 301,
+        }),
+    },
+    Case {
+        name: "immediate write",
+        source: "data 11101, 1, 1, 0",
+        inputs: &[],
+        expected: Err(Error::ImmediateWrite { address: 0 }),
+    },
+    Case {
+        name: "negative position",
+        source: "out [-1]",
+        inputs: &[],
+        expected: Err(Error::NegativeAddress {
+            address: 0,
+            target: -1,
+        }),
+    },
+    Case {
+        name: "negative relative",
+        source: "
+            arb -5
+            out [rb+2]
+        ",
+        inputs: &[],
+        expected: Err(Error::NegativeAddress {
+            address: 2,
+            target: -3,
+        }),
+    },
+    Case {
+        name: "negative jump",
+        source: "jnz 1, -4",
+        inputs: &[],
+        expected: Err(Error::NegativeAddress {
+            address: 0,
+            target: -4,
+        }),
+    },
+    Case {
+        name: "overflowing mul",
+        source: "
+            mul 9223372036854775807, 2, [0]
+            hlt
+        ",
+        inputs: &[],
+        expected: Err(Error::Overflow { address: 0 }),
+    },
+    Case {
+        name: "overflowing add",
+        source: "
+            out 1
+            add -9223372036854775808, -1, [0]
+            hlt
+        ",
+        inputs: &[],
+        expected: Err(Error::Overflow { address: 2 }),
+    },
+    Case {
+        name: "overflowing relative base",
+        source: "
+            arb 9223372036854775807
+            arb 1
+        ",
+        inputs: &[],
+        expected: Err(Error::Overflow { address: 2 }),
+    },
+    Case {
+        name: "overflowing relative address",
+        source: "
+            arb 9223372036854775807
+            out [rb+1]
+        ",
+        inputs: &[],
+        expected: Err(Error::Overflow { address: 2 }),
+    },
+    Case {
+        name: "out of input",
+        source: "
+            in [0]
+            in [0]
+            hlt
+        ",
+        inputs: &[1],
+        expected: Err(Error::NoInput { address: 2 }),
+    },
+];
+
+#[test]
+fn test_cases() {
+    for case in CASES {
+        let memory = assemble(case.source)
+            .unwrap_or_else(|error| panic!("{}: doesn't assemble: {error}", case.name));
+        let mut intcode = IntCode::new(memory);
+        intcode.push_inputs(case.inputs.iter().copied());
+        let mut cached = CachedIntCode::new(intcode.clone());
+        let expected = case.expected.clone().map(<[i64]>::to_vec);
+        assert_eq!(intcode.run_to_halt(), expected, "{}", case.name);
+        assert_eq!(cached.run_to_halt(), expected, "{} (cached)", case.name);
+    }
+}
+
+#[test]
+fn test_every_mode() {
+    let reads = [("[vals]", "[vals+1]"), ("6", "7"), ("[rb+0]", "[rb+1]")];
+    let writes = ["[vals+2]", "[rb+2]"];
+    let ops = [
+        (Op::Add, 13),
+        (Op::Mul, 42),
+        (Op::LessThan, 1),
+        (Op::Equals, 0),
+    ];
+    for (op, expected) in ops {
+        for (a, _) in reads {
+            for (_, b) in reads {
+                for dest in writes {
+                    let source = format!(
+                        "
+                            arb vals
+                            {} {a}, {b}, {dest}
+                            out {dest}
+                            hlt
+                        vals: data 6, 7, 0
+                        ",
+                        op.mnemonic()
+                    );
+                    let mut intcode = IntCode::new(assemble(&source).unwrap());
+                    assert_eq!(intcode.run_to_halt(), Ok(vec![expected]), "{source}");
+                }
+            }
+        }
+    }
+}
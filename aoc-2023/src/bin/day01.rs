@@ -38,7 +38,7 @@ pub fn main() {
             // eprintln!("{line}");
             let mut citer = line.chars().filter(char::is_ascii_digit);
             let dig1 = citer.next().expect("Must have at least one charecter");
-            let dig2 = citer.last().unwrap_or(dig1);
+            let dig2 = citer.next_back().unwrap_or(dig1);
             format!("{dig1}{dig2}").parse::<u32>().unwrap()
         })
         .sum();
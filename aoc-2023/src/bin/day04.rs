@@ -2,7 +2,7 @@
 
 use std::collections::HashSet;
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::struct_field_names)]
 struct Card {
     id: u32,
     winning_numbers: HashSet<u32>,
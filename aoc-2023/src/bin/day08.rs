@@ -48,8 +48,7 @@ fn main() {
     println!(
         "Day 08 result: {}",
         found_nodepaths
-            .iter()
-            .map(|&s| s as u64)
+            .into_iter()
             // math is fun!
             // (also the problems must be constructed so this works,
             // it's not hard to construct a counter example)
@@ -2,6 +2,8 @@
 
 use std::collections::HashSet;
 
+use aoc_helpers::geometry::Polygon;
+
 #[derive(PartialEq, Debug)]
 enum Directions {
     North,
@@ -59,6 +61,8 @@ impl Grid {
             .collect()
     }
 
+    /// Only used for pprint now, the enclosed count comes from [`Self::loop_polygon`]
+    #[allow(dead_code)]
     fn enclosed_coords(&self) -> Vec<(usize, usize)> {
         let loop_coords = self.loop_coordinates();
         let clean_board: Vec<Vec<_>> = self.cleaned_board();
@@ -122,6 +126,21 @@ impl Grid {
     }
 
     fn loop_coordinates(&self) -> HashSet<(usize, usize)> {
+        self.loop_path().into_iter().collect()
+    }
+
+    /// The loop as a polygon through the centres of its tiles, so the enclosed tiles are the
+    /// lattice points strictly inside it
+    fn loop_polygon(&self) -> Polygon {
+        Polygon::from_vertices(
+            self.loop_path()
+                .into_iter()
+                .map(|(x, y)| (i64::try_from(x).unwrap(), i64::try_from(y).unwrap())),
+        )
+    }
+
+    /// Every tile on the loop, in the order they're walked starting from S
+    fn loop_path(&self) -> Vec<(usize, usize)> {
         let mut cur_coords =
             self.tiles
                 .iter()
@@ -139,6 +158,7 @@ impl Grid {
                     ))
                 })
                 .unwrap();
+        let mut path = vec![];
         let mut found_coords = HashSet::new();
         loop {
             let valid_neighbors: Vec<(usize, usize)> =
@@ -149,13 +169,14 @@ impl Grid {
                 "There can only be exactly 2 neighbors in a valid loop"
             );
             found_coords.insert(cur_coords);
+            path.push(cur_coords);
             if let Some(&new_coords) = valid_neighbors.iter().find(|c| !found_coords.contains(c)) {
                 cur_coords = new_coords;
             } else {
                 break;
             }
         }
-        found_coords
+        path
     }
 
     fn tile_at(&self, x: usize, y: usize) -> Option<&char> {
@@ -238,6 +259,6 @@ impl Grid {
 fn main() -> anyhow::Result<()> {
     let grid: Grid = aoc_helpers::include_data!(day10).try_into()?;
     // grid.pprint();
-    println!("Day 10 result: {}", grid.enclosed_coords().len());
+    println!("Day 10 result: {}", grid.loop_polygon().interior_points());
     Ok(())
 }
@@ -101,7 +101,7 @@ impl Universe {
     #[allow(dead_code)]
     fn pprint(&self) {
         for row in &self.data {
-            let n = match row.get(0) {
+            let n = match row.first() {
                 Some(Point::Empty(_, n)) => *n,
                 Some(Point::Galaxy) => 1,
                 None => unreachable!(),
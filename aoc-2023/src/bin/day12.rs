@@ -99,10 +99,10 @@ fn can_place_group_at(elems: &[Space], group: &SpringGroup, idx: usize) -> bool
 
 // interesting here is the next Spring | Unknown after (or at) the given idx
 // so e.g. .??....???? -> vec![1, 1, 2, 7, 7, 7, 7, 7, 7, 7, 8, 9, 10] (all somes)
-#[cached::proc_macro::cached]
+#[aoc_macros::memoize]
 fn find_number_possible_group_locations(
-    elems: Vec<Space>,
-    groups: Vec<SpringGroup>,
+    elems: &[Space],
+    groups: &[SpringGroup],
     cur_idx: usize,
     cur_group_idx: usize,
 ) -> usize {
@@ -159,12 +159,8 @@ fn find_number_possible_group_locations(
         }
     }
     for (found, occurances) in &interesting {
-        let v_interesting = find_number_possible_group_locations(
-            elems.clone(),
-            groups.clone(),
-            *found,
-            cur_group_idx + 1,
-        );
+        let v_interesting =
+            find_number_possible_group_locations(elems, groups, *found, cur_group_idx + 1);
         tot += v_interesting * occurances;
     }
     tot
@@ -172,7 +168,7 @@ fn find_number_possible_group_locations(
 
 impl Row {
     fn number_possible_springgroup_locations(&self) -> usize {
-        find_number_possible_group_locations(self.elems.clone(), self.groups.clone(), 0, 0)
+        find_number_possible_group_locations(&self.elems, &self.groups, 0, 0)
     }
 }
 
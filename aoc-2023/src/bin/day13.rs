@@ -49,7 +49,7 @@ fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
         .collect()
 }
 
-fn find_horizontal_mirrored_line(elems: &Vec<Vec<Point>>) -> Option<usize> {
+fn find_horizontal_mirrored_line(elems: &[Vec<Point>]) -> Option<usize> {
     let len = elems.len();
     for line in 0..elems.len() {
         let num_mirrored_lines = (len - (line + 1)).min(line + 1);
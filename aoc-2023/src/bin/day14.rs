@@ -1,6 +1,6 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
-use std::{collections::HashSet, fmt::Display};
+use std::fmt::Display;
 
 #[derive(Clone, Hash, Eq, PartialEq)]
 enum Space {
@@ -20,6 +20,7 @@ impl From<char> for Space {
     }
 }
 
+#[derive(Clone, Hash, Eq, PartialEq)]
 struct Board {
     elems: Vec<Vec<Space>>,
 }
@@ -193,35 +194,13 @@ impl Board {
 }
 
 fn main() {
-    let mut board: Board = aoc_helpers::include_data!(day14).into();
-    // guessing that total number of enountered boardstates is < 2000, purely for performance
-    // benefits
-    let mut known_boardstates = HashSet::with_capacity(1_000);
-    let mut till_first_cycle_starts = None;
-    let mut main_cycle_len = None;
-    let target = 1_000_000_000;
-    for i in 0.. {
+    let board: Board = aoc_helpers::include_data!(day14).into();
+    let cycles = aoc_helpers::math::find_cycle(board, |board| {
+        let mut board = board.clone();
         board.cycle();
-        if known_boardstates.contains(&board.elems) {
-            if let Some(first_cycle) = till_first_cycle_starts {
-                main_cycle_len = Some(i - first_cycle);
-                break;
-            }
-            till_first_cycle_starts = Some(i);
-            known_boardstates.clear();
-        }
-        known_boardstates.insert(board.elems.clone());
-    }
-    let till_first_cycle_starts = till_first_cycle_starts.unwrap();
-    let main_cycle_len = main_cycle_len.unwrap();
-    let target_minus_init = target - till_first_cycle_starts;
-    let remaining = target_minus_init % main_cycle_len;
-
-    // -1 because we've done till_first_cycle_starts + main_cycle_len + 1 by a "quirk" of how the loop above was written
-    // (by which I mean, confusingly)
-    for _ in 0..remaining - 1 {
-        board.cycle();
-    }
+        board
+    });
+    let board = cycles.state_at(1_000_000_000);
     // println!("{board}");
     println!("Day 14 result: {}", board.load());
 }
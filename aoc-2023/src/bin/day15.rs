@@ -24,7 +24,7 @@ impl From<&str> for Lense {
             _ => unreachable!(),
         };
         Self {
-            label: label.to_string(),
+            label: label.clone(),
             op,
             box_number: hasher(&label),
         }
@@ -3,12 +3,6 @@
 type Pos = (usize, usize);
 type State = (Pos, Option<Direction>, usize);
 
-#[derive(PartialEq, Hash, Eq, Debug, Copy, Clone)]
-struct Node {
-    cost: i32,
-    pos: Pos,
-}
-
 #[derive(Eq, Hash, PartialEq, Clone, Debug)]
 enum Direction {
     Up,
@@ -33,10 +27,8 @@ impl Direction {
     }
 }
 
-fn nexts<F>(weight: &F, state: &State) -> Vec<(i32, State)>
-where
-    F: Fn(&Pos) -> Option<i32>,
-{
+fn nexts(map: &[Vec<usize>], state: &State) -> Vec<(State, usize)> {
+    let weight = |pos: &Pos| Some(*map.get(pos.1)?.get(pos.0)?);
     let mut nexts = vec![];
     let next_dirs = state
         .1
@@ -47,7 +39,7 @@ where
             if let Some(pos) = dir.add(state.0, 1) {
                 let new_state = (pos, Some(dir.clone()), state.2 + 1);
                 if let Some(w) = weight(&pos) {
-                    nexts.push((w, new_state));
+                    nexts.push((new_state, w));
                 }
             }
         }
@@ -86,7 +78,7 @@ where
                 })
             };
             if let Some(w) = w() {
-                nexts.push((w, (pos, Some(dir), 4)));
+                nexts.push(((pos, Some(dir), 4), w));
             }
         }
     }
@@ -99,18 +91,18 @@ fn main() {
         .lines()
         .map(|line| {
             line.chars()
-                .map(|c| i32::try_from(c.to_digit(10).unwrap()).unwrap())
+                .map(|c| c.to_digit(10).unwrap() as usize)
                 .collect()
         })
         .collect();
     let dest = (map[0].len() - 1, map.len() - 1);
     let (_path, result) = aoc_helpers::math::astar(
         &((0, 0), None, 0),
-        nexts,
+        |state| nexts(&map, state),
         |elem| elem.0 == dest,
-        move |pos: &Pos| Some(*map.get(pos.1)?.get(pos.0)?),
-        |(pos, _, _)| i32::try_from(pos.0.abs_diff(dest.0) + pos.1.abs_diff(dest.1)).unwrap(),
-    );
+        |(pos, _, _)| pos.0.abs_diff(dest.0) + pos.1.abs_diff(dest.1),
+    )
+    .unwrap();
     // println!("{path:?}");
     println!("Day 17 result: {result}");
 }
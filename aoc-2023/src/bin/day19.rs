@@ -2,6 +2,9 @@
 
 use std::{cmp::Ordering, collections::HashMap};
 
+use anyhow::{anyhow, bail, Context};
+use aoc_helpers::hyperrect::Hyperrect;
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 enum RuleResult {
     Accept,
@@ -19,53 +22,72 @@ impl From<&str> for RuleResult {
     }
 }
 
+/// Index of a rating in [`Part::ratings`], and the axis it's on in a [`Hyperrect`]
+fn axis(field: &str) -> anyhow::Result<usize> {
+    match field {
+        "x" => Ok(0),
+        "m" => Ok(1),
+        "a" => Ok(2),
+        "s" => Ok(3),
+        _ => bail!("unknown rating {field:?}"),
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 struct Part {
-    x: usize,
-    m: usize,
-    a: usize,
-    s: usize,
+    ratings: [i64; 4],
 }
 
 #[allow(dead_code)]
 impl Part {
-    const fn rating(&self) -> usize {
-        self.x + self.m + self.a + self.s
+    fn rating(&self) -> i64 {
+        self.ratings.iter().sum()
     }
 }
 
 const CURLIES: &[char] = &['{', '}'];
 
-#[allow(clippy::many_single_char_names, clippy::fallible_impl_from)]
-impl From<&str> for Part {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Part {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
         let value = value.trim_matches(CURLIES);
-        let (mut x, mut m, mut a, mut s) = (None, None, None, None);
+        let mut ratings = [None; 4];
         for v in value.split(',') {
-            let (k, v) = v.split_once('=').unwrap();
-            match k {
-                "x" => x = Some(v.parse().unwrap()),
-                "m" => m = Some(v.parse().unwrap()),
-                "a" => a = Some(v.parse().unwrap()),
-                "s" => s = Some(v.parse().unwrap()),
-                _ => unreachable!(),
-            }
-        }
-        Self {
-            x: x.unwrap(),
-            m: m.unwrap(),
-            a: a.unwrap(),
-            s: s.unwrap(),
+            let (k, v) = v
+                .split_once('=')
+                .with_context(|| format!("bad rating {v:?}"))?;
+            ratings[axis(k)?] = Some(v.parse()?);
         }
+        let [Some(x), Some(m), Some(a), Some(s)] = ratings else {
+            bail!("missing ratings in {value:?}");
+        };
+        Ok(Self {
+            ratings: [x, m, a, s],
+        })
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
 struct RuleCond {
-    field: String,
+    axis: usize,
     op: Ordering,
-    val: usize,
+    val: i64,
+}
+
+impl RuleCond {
+    /// Splits `ranges` into the part matching this condition and the part that doesn't
+    fn split(&self, ranges: &Hyperrect<4>) -> (Option<Hyperrect<4>>, Option<Hyperrect<4>>) {
+        match self.op {
+            Ordering::Greater => {
+                let (not_matches, matches) = ranges.split(self.axis, self.val + 1);
+                (matches, not_matches)
+            }
+            Ordering::Less => ranges.split(self.axis, self.val),
+            Ordering::Equal => unreachable!(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -77,14 +99,8 @@ struct Rule {
 #[allow(dead_code)]
 impl Rule {
     fn eval(&self, part: &Part) -> bool {
-        self.condition.as_ref().map_or(true, |cond| {
-            let v = match &cond.field[..] {
-                "x" => part.x,
-                "m" => part.m,
-                "a" => part.a,
-                "s" => part.s,
-                _ => unreachable!(),
-            };
+        self.condition.as_ref().is_none_or(|cond| {
+            let v = part.ratings[cond.axis];
             match cond.op {
                 Ordering::Less => v < cond.val,
                 Ordering::Greater => v > cond.val,
@@ -94,33 +110,31 @@ impl Rule {
     }
 }
 
-#[allow(clippy::fallible_impl_from)]
-impl From<&str> for Rule {
-    fn from(value: &str) -> Self {
-        if let Some((condition, result)) = value.split_once(':') {
-            let (field, op, val) = if condition.contains('>') {
-                let (v, n) = condition.split_once('>').unwrap();
-                let v = v.to_string();
-                let n = n.parse().unwrap();
-                (v, Ordering::Greater, n)
-            } else if condition.contains('<') {
-                let (v, n) = condition.split_once('<').unwrap();
-                let v = v.to_string();
-                let n = n.parse().unwrap();
-                (v, Ordering::Less, n)
-            } else {
-                unreachable!()
-            };
-            Self {
-                condition: Some(RuleCond { field, op, val }),
-                result: result.into(),
-            }
-        } else {
-            Self {
+impl TryFrom<&str> for Rule {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let Some((condition, result)) = value.split_once(':') else {
+            return Ok(Self {
                 condition: None,
                 result: value.into(),
-            }
-        }
+            });
+        };
+        let (op, (v, n)) = if let Some(split) = condition.split_once('>') {
+            (Ordering::Greater, split)
+        } else if let Some(split) = condition.split_once('<') {
+            (Ordering::Less, split)
+        } else {
+            bail!("no comparison in {condition:?}");
+        };
+        Ok(Self {
+            condition: Some(RuleCond {
+                axis: axis(v)?,
+                op,
+                val: n.parse()?,
+            }),
+            result: result.into(),
+        })
     }
 }
 
@@ -142,116 +156,103 @@ impl Workflow {
     }
 }
 
-#[allow(clippy::fallible_impl_from)]
-impl From<&str> for Workflow {
-    fn from(value: &str) -> Self {
-        let (name, rules) = value.split_once('{').unwrap();
-        let rules = rules.strip_suffix('}').unwrap().split(',');
-        Self {
-            name: name.to_string(),
-            rules: rules.map(Into::into).collect(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-struct ValueRanges {
-    x: (usize, usize),
-    s: (usize, usize),
-    m: (usize, usize),
-    a: (usize, usize),
-}
+impl TryFrom<&str> for Workflow {
+    type Error = anyhow::Error;
 
-impl Default for ValueRanges {
-    fn default() -> Self {
-        Self {
-            x: (1, 4000),
-            m: (1, 4000),
-            s: (1, 4000),
-            a: (1, 4000),
-        }
-    }
-}
-
-impl ValueRanges {
-    const fn num_cases(&self) -> usize {
-        (self.x.1 - self.x.0 + 1)
-            * (self.s.1 - self.s.0 + 1)
-            * (self.m.1 - self.m.0 + 1)
-            * (self.a.1 - self.a.0 + 1)
-    }
-
-    fn split_on_restriction(&self, condition: &RuleCond) -> (Self, Self) {
-        let mut matches = self.clone();
-        let mut not_matches = self.clone();
-        let (field, target_matches_field, target_not_matches_field) = match &condition.field[..] {
-            "x" => (self.x, &mut matches.x, &mut not_matches.x),
-            "s" => (self.s, &mut matches.s, &mut not_matches.s),
-            "m" => (self.m, &mut matches.m, &mut not_matches.m),
-            "a" => (self.a, &mut matches.a, &mut not_matches.a),
-            _ => unreachable!(),
-        };
-        match condition.op {
-            Ordering::Greater => {
-                *target_matches_field = (condition.val + 1, field.1);
-                *target_not_matches_field = (field.0, condition.val);
-            }
-            Ordering::Less => {
-                *target_matches_field = (field.0, condition.val - 1);
-                *target_not_matches_field = (condition.val, field.1);
-            }
-            Ordering::Equal => unreachable!(),
-        }
-        (matches, not_matches)
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let rules = value
+            .split_once('{')
+            .and_then(|(name, rules)| Some((name, rules.strip_suffix('}')?)));
+        let (name, rules) = rules.ok_or_else(|| anyhow!("bad workflow {value:?}"))?;
+        Ok(Self {
+            name: name.to_string(),
+            rules: rules
+                .split(',')
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
 fn solve_part_2(
     workflows: &HashMap<String, Workflow>,
     next_workflow: &String,
-    mut ranges: ValueRanges,
-) -> usize {
+    ranges: Hyperrect<4>,
+) -> i64 {
     // Ok so, important fact that I needed to verify: for ANY workspace, it is mentioned in exactly
     // 1 other workspace's rules (except in, which is the entry point). SO we can say that any
     //   given workspace narrows the possible accepts (ValueRanges) by each rule, passing that
     //   acceptable ranges to the child workspace of that rule and using the unacceptable range for
-    //   the remaining rules, for each rule. Then we can just add up the product of the 4 acceptable ranges for each
+    //   the remaining rules, for each rule. Then we can just add up the volume of the acceptable ranges for each
     //   workspace, eventually getting the total count of acceptable values.
     let mut found_accepts = 0;
+    let mut remaining = Some(ranges);
     for rule in &workflows[next_workflow].rules {
-        if let Some(cond) = &rule.condition {
-            let (matches, not_matches) = ranges.split_on_restriction(cond);
-            match &rule.result {
-                RuleResult::Accept => found_accepts += matches.num_cases(),
-                RuleResult::Reject => (),
-                RuleResult::Workflow(next) => {
-                    found_accepts += solve_part_2(workflows, next, matches);
-                }
-            }
-            ranges = not_matches;
+        let Some(ranges) = remaining.take() else {
+            break;
+        };
+        let matches = if let Some(cond) = &rule.condition {
+            let (matches, not_matches) = cond.split(&ranges);
+            remaining = not_matches;
+            matches
         } else {
-            match &rule.result {
-                RuleResult::Accept => found_accepts += ranges.num_cases(),
-                RuleResult::Reject => (),
-                RuleResult::Workflow(next) => {
-                    found_accepts += solve_part_2(workflows, next, ranges.clone());
-                }
+            Some(ranges)
+        };
+        let Some(matches) = matches else {
+            continue;
+        };
+        match &rule.result {
+            RuleResult::Accept => found_accepts += matches.volume(),
+            RuleResult::Reject => (),
+            RuleResult::Workflow(next) => {
+                found_accepts += solve_part_2(workflows, next, matches);
             }
         }
     }
     found_accepts
 }
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let (workflows, _parts) = aoc_helpers::include_data!(day19)
         .split_once("\n\n")
-        .unwrap();
+        .context("no blank line between workflows and parts")?;
 
     let workflows: HashMap<_, _> = workflows
         .lines()
-        .map(Into::into)
-        .map(|w: Workflow| (w.name.clone(), w))
-        .collect();
-    let result = solve_part_2(&workflows, &"in".to_string(), ValueRanges::default());
+        .map(Workflow::try_from)
+        .map(|w| w.map(|w| (w.name.clone(), w)))
+        .collect::<anyhow::Result<_>>()?;
+    let result = solve_part_2(&workflows, &"in".to_string(), Hyperrect::cube(1..4001));
     println!("Day 19 result: {result}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let workflow = Workflow::try_from("px{a<2006:qkq,m>2090:A,rfg}").unwrap();
+        assert_eq!(workflow.rules.len(), 3);
+        assert_eq!(
+            workflow.rules[1].condition,
+            Some(RuleCond {
+                axis: 1,
+                op: Ordering::Greater,
+                val: 2090
+            })
+        );
+        let part = Part::try_from("{x=787,m=2655,a=1222,s=2876}").unwrap();
+        assert_eq!(part.ratings, [787, 2655, 1222, 2876]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Workflow::try_from("px{q<2006:qkq,rfg}").is_err());
+        assert!(Workflow::try_from("px{a=2006:qkq,rfg}").is_err());
+        assert!(Workflow::try_from("px{a<2006:qkq,rfg").is_err());
+        assert!(Part::try_from("{x=787,m=2655,q=1222,s=2876}").is_err());
+        assert!(Part::try_from("{x=787,m=2655,a=1222}").is_err());
+    }
 }
@@ -0,0 +1,5 @@
+pub mod lines;
+pub mod polygon;
+
+pub use lines::{Extent, Intersection, Line2, Line3, Ratio};
+pub use polygon::{Point, Polygon};
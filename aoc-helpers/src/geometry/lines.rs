@@ -0,0 +1,508 @@
+//! Where lines, rays and segments meet, in 2D or 3D, without any floating point.
+//!
+//! Everything is `origin + t * direction` with integer origin and direction, and `t` comes back
+//! as an exact [`Ratio`]. Intermediate products are `i128`, which is plenty for hailstone sized
+//! (~1e15) positions.
+
+use std::cmp::Ordering;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::math::gcd;
+
+/// A fraction kept in lowest terms with a positive denominator, so `==` and `Hash` just work
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    pub const ZERO: Self = Self { num: 0, den: 1 };
+    pub const ONE: Self = Self { num: 1, den: 1 };
+
+    /// # Panics
+    /// If `den` is 0
+    #[must_use]
+    pub fn new(num: i128, den: i128) -> Self {
+        assert_ne!(den, 0, "zero denominator");
+        let sign = den.signum();
+        let divisor = gcd(num, den);
+        Self {
+            num: sign * num / divisor,
+            den: sign * den / divisor,
+        }
+    }
+
+    #[must_use]
+    pub const fn numerator(&self) -> i128 {
+        self.num
+    }
+
+    #[must_use]
+    pub const fn denominator(&self) -> i128 {
+        self.den
+    }
+
+    #[must_use]
+    pub const fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// `None` if it isn't a whole number
+    #[must_use]
+    pub const fn to_integer(&self) -> Option<i128> {
+        if self.is_integer() {
+            Some(self.num)
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl From<i128> for Ratio {
+    fn from(value: i128) -> Self {
+        Self { num: value, den: 1 }
+    }
+}
+
+impl From<i64> for Ratio {
+    fn from(value: i64) -> Self {
+        i128::from(value).into()
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are positive so this doesn't flip anything
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Ratio {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.num * other.den + other.num * self.den,
+            self.den * other.den,
+        )
+    }
+}
+
+impl Sub for Ratio {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.num * other.num, self.den * other.den)
+    }
+}
+
+impl Div for Ratio {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        Self::new(self.num * other.den, self.den * other.num)
+    }
+}
+
+impl Neg for Ratio {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+/// Which values of `t` are part of the shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Extent {
+    /// `0 <= t <= 1`, so from `origin` to `origin + direction` inclusive
+    Segment,
+    /// `t >= 0`, e.g. a hailstone's future
+    Ray,
+    /// Any `t`
+    Line,
+}
+
+impl Extent {
+    /// Lowest and highest `t`, `None` meaning unbounded
+    const fn bounds(self) -> (Option<Ratio>, Option<Ratio>) {
+        match self {
+            Self::Segment => (Some(Ratio::ZERO), Some(Ratio::ONE)),
+            Self::Ray => (Some(Ratio::ZERO), None),
+            Self::Line => (None, None),
+        }
+    }
+
+    fn contains(self, t: Ratio) -> bool {
+        let (low, high) = self.bounds();
+        low.is_none_or(|low| low <= t) && high.is_none_or(|high| t <= high)
+    }
+}
+
+/// How two shapes meet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intersection<P> {
+    /// They don't: parallel, skew, or the lines cross outside one of the segments/rays
+    None,
+    /// Exactly one shared point, at `t` along the first and `u` along the second
+    Point { point: P, t: Ratio, u: Ratio },
+    /// Collinear and sharing more than one point, given as the range of `t` along the first
+    /// (`None` meaning unbounded in that direction)
+    Overlap {
+        start: Option<Ratio>,
+        end: Option<Ratio>,
+    },
+}
+
+type Vector3 = [i128; 3];
+
+const fn sub(a: Vector3, b: Vector3) -> Vector3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+const fn dot(a: Vector3, b: Vector3) -> i128 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+const fn cross(a: Vector3, b: Vector3) -> Vector3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// The shared maths, 2D shapes are done as 3D ones with `z = 0`. Returns the `t` and `u` of the
+/// meeting point rather than the point itself.
+fn intersect(
+    (origin, direction, extent): (Vector3, Vector3, Extent),
+    (other_origin, other_direction, other_extent): (Vector3, Vector3, Extent),
+) -> Intersection<()> {
+    let offset = sub(other_origin, origin);
+    let normal = cross(direction, other_direction);
+    if normal == [0; 3] {
+        if cross(offset, direction) != [0; 3] {
+            // parallel, but not on the same line
+            return Intersection::None;
+        }
+        return collinear(direction, extent, offset, other_direction, other_extent);
+    }
+    if dot(offset, normal) != 0 {
+        // skew
+        return Intersection::None;
+    }
+    let normal_squared = dot(normal, normal);
+    let t = Ratio::new(dot(cross(offset, other_direction), normal), normal_squared);
+    let u = Ratio::new(dot(cross(offset, direction), normal), normal_squared);
+    if extent.contains(t) && other_extent.contains(u) {
+        Intersection::Point { point: (), t, u }
+    } else {
+        Intersection::None
+    }
+}
+
+/// Both on the same line, so work out which stretch of it they share
+fn collinear(
+    direction: Vector3,
+    extent: Extent,
+    offset: Vector3,
+    other_direction: Vector3,
+    other_extent: Extent,
+) -> Intersection<()> {
+    // other's point at u is at t = start + u * scale along self
+    let length_squared = dot(direction, direction);
+    let start = Ratio::new(dot(offset, direction), length_squared);
+    let scale = Ratio::new(dot(other_direction, direction), length_squared);
+    let to_t = |u: Option<Ratio>| u.map(|u| start + u * scale);
+    let (other_low, other_high) = other_extent.bounds();
+    let (other_low, other_high) = if scale > Ratio::ZERO {
+        (to_t(other_low), to_t(other_high))
+    } else {
+        (to_t(other_high), to_t(other_low))
+    };
+    let (low, high) = extent.bounds();
+    // None is less than any Some, so this keeps the tighter bound
+    let low = low.max(other_low);
+    let high = match (high, other_high) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    match (low, high) {
+        (Some(low), Some(high)) if low > high => Intersection::None,
+        (Some(low), Some(high)) if low == high => Intersection::Point {
+            point: (),
+            t: low,
+            u: (low - start) / scale,
+        },
+        (start, end) => Intersection::Overlap { start, end },
+    }
+}
+
+fn point_at<const N: usize>(origin: [i64; N], direction: [i64; N], t: Ratio) -> [Ratio; N] {
+    std::array::from_fn(|i| Ratio::from(origin[i]) + Ratio::from(direction[i]) * t)
+}
+
+/// A 2D line, ray or segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Line2 {
+    pub origin: [i64; 2],
+    pub direction: [i64; 2],
+    pub extent: Extent,
+}
+
+impl Line2 {
+    #[must_use]
+    pub const fn segment(start: [i64; 2], end: [i64; 2]) -> Self {
+        Self {
+            origin: start,
+            direction: [end[0] - start[0], end[1] - start[1]],
+            extent: Extent::Segment,
+        }
+    }
+
+    #[must_use]
+    pub const fn ray(origin: [i64; 2], direction: [i64; 2]) -> Self {
+        Self {
+            origin,
+            direction,
+            extent: Extent::Ray,
+        }
+    }
+
+    #[must_use]
+    pub const fn line(origin: [i64; 2], direction: [i64; 2]) -> Self {
+        Self {
+            origin,
+            direction,
+            extent: Extent::Line,
+        }
+    }
+
+    #[must_use]
+    pub fn point_at(&self, t: Ratio) -> [Ratio; 2] {
+        point_at(self.origin, self.direction, t)
+    }
+
+    fn as_3d(&self) -> (Vector3, Vector3, Extent) {
+        let [x, y] = self.origin.map(i128::from);
+        let [dx, dy] = self.direction.map(i128::from);
+        ([x, y, 0], [dx, dy, 0], self.extent)
+    }
+
+    /// Whether the directions are parallel (or opposite), collinear or not
+    #[must_use]
+    pub fn is_parallel(&self, other: &Self) -> bool {
+        let (_, direction, _) = self.as_3d();
+        let (_, other_direction, _) = other.as_3d();
+        cross(direction, other_direction) == [0; 3]
+    }
+
+    /// Direction should be non-zero for both, a segment from a point to itself isn't handled
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Intersection<[Ratio; 2]> {
+        match intersect(self.as_3d(), other.as_3d()) {
+            Intersection::None => Intersection::None,
+            Intersection::Point { t, u, .. } => Intersection::Point {
+                point: self.point_at(t),
+                t,
+                u,
+            },
+            Intersection::Overlap { start, end } => Intersection::Overlap { start, end },
+        }
+    }
+}
+
+/// A 3D line, ray or segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Line3 {
+    pub origin: [i64; 3],
+    pub direction: [i64; 3],
+    pub extent: Extent,
+}
+
+impl Line3 {
+    #[must_use]
+    pub const fn segment(start: [i64; 3], end: [i64; 3]) -> Self {
+        Self {
+            origin: start,
+            direction: [end[0] - start[0], end[1] - start[1], end[2] - start[2]],
+            extent: Extent::Segment,
+        }
+    }
+
+    #[must_use]
+    pub const fn ray(origin: [i64; 3], direction: [i64; 3]) -> Self {
+        Self {
+            origin,
+            direction,
+            extent: Extent::Ray,
+        }
+    }
+
+    #[must_use]
+    pub const fn line(origin: [i64; 3], direction: [i64; 3]) -> Self {
+        Self {
+            origin,
+            direction,
+            extent: Extent::Line,
+        }
+    }
+
+    #[must_use]
+    pub fn point_at(&self, t: Ratio) -> [Ratio; 3] {
+        point_at(self.origin, self.direction, t)
+    }
+
+    fn as_3d(&self) -> (Vector3, Vector3, Extent) {
+        (
+            self.origin.map(i128::from),
+            self.direction.map(i128::from),
+            self.extent,
+        )
+    }
+
+    /// Direction should be non-zero for both, a segment from a point to itself isn't handled
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Intersection<[Ratio; 3]> {
+        match intersect(self.as_3d(), other.as_3d()) {
+            Intersection::None => Intersection::None,
+            Intersection::Point { t, u, .. } => Intersection::Point {
+                point: self.point_at(t),
+                t,
+                u,
+            },
+            Intersection::Overlap { start, end } => Intersection::Overlap { start, end },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratio(num: i128, den: i128) -> Ratio {
+        Ratio::new(num, den)
+    }
+
+    fn whole<const N: usize>(coords: [i64; N]) -> [Ratio; N] {
+        coords.map(Ratio::from)
+    }
+
+    #[test]
+    fn test_ratio() {
+        assert_eq!(ratio(4, -6), ratio(-2, 3));
+        assert!(ratio(1, 3) < ratio(1, 2));
+        assert_eq!(ratio(1, 3) + ratio(1, 6), ratio(1, 2));
+        assert_eq!(ratio(1, 2) / ratio(1, 4), Ratio::from(2_i64));
+        assert_eq!(ratio(6, 3).to_integer(), Some(2));
+        assert_eq!(ratio(7, 3).to_integer(), None);
+    }
+
+    #[test]
+    fn test_segments_crossing() {
+        let vert = Line2::segment([5, 5], [5, 20]);
+        let horiz = Line2::segment([3, 10], [10, 10]);
+        let Intersection::Point { point, t, u } = vert.intersect(&horiz) else {
+            panic!("should cross");
+        };
+        assert_eq!(point, whole([5, 10]));
+        assert_eq!((t, u), (ratio(1, 3), ratio(2, 7)));
+        // the lines cross, but not within the second segment
+        let short = Line2::segment([7, 10], [10, 10]);
+        assert_eq!(vert.intersect(&short), Intersection::None);
+        // touching at an end counts
+        let touching = Line2::segment([5, 20], [9, 24]);
+        assert!(matches!(
+            vert.intersect(&touching),
+            Intersection::Point { t, .. } if t == Ratio::ONE
+        ));
+    }
+
+    #[test]
+    fn test_parallel_and_collinear() {
+        let a = Line2::segment([0, 0], [4, 4]);
+        assert_eq!(
+            a.intersect(&Line2::segment([0, 1], [4, 5])),
+            Intersection::None
+        );
+        assert_eq!(
+            a.intersect(&Line2::segment([6, 6], [2, 2])),
+            Intersection::Overlap {
+                start: Some(ratio(1, 2)),
+                end: Some(Ratio::ONE)
+            }
+        );
+        assert_eq!(
+            a.intersect(&Line2::ray([4, 4], [1, 1])),
+            Intersection::Point {
+                point: whole([4, 4]),
+                t: Ratio::ONE,
+                u: Ratio::ZERO
+            }
+        );
+        assert_eq!(
+            Line2::line([0, 0], [1, 1]).intersect(&Line2::ray([2, 2], [-1, -1])),
+            Intersection::Overlap {
+                start: None,
+                end: Some(Ratio::from(2_i64))
+            }
+        );
+        assert_eq!(a.intersect(&Line2::ray([5, 5], [1, 1])), Intersection::None);
+        assert!(a.is_parallel(&Line2::ray([5, 5], [-2, -2])));
+        assert!(!a.is_parallel(&Line2::ray([5, 5], [1, 2])));
+    }
+
+    #[test]
+    fn test_hailstone_rays() {
+        // 2023 day 24 example, first pair: paths cross at x=14.333, y=15.333
+        let a = Line2::ray([19, 13], [-2, 1]);
+        let b = Line2::ray([18, 19], [-1, -1]);
+        let Intersection::Point { point, .. } = a.intersect(&b) else {
+            panic!("should cross");
+        };
+        assert_eq!(point, [ratio(43, 3), ratio(46, 3)]);
+        // crossed in the past for the first
+        let c = Line2::ray([20, 19], [1, -5]);
+        assert_eq!(a.intersect(&c), Intersection::None);
+        assert!(matches!(
+            Line2::line(a.origin, a.direction).intersect(&Line2::line(c.origin, c.direction)),
+            Intersection::Point { t, .. } if t < Ratio::ZERO
+        ));
+    }
+
+    #[test]
+    fn test_3d() {
+        let a = Line3::line([0, 0, 0], [1, 1, 1]);
+        let b = Line3::line([2, 0, 2], [0, 1, 0]);
+        assert_eq!(
+            a.intersect(&b),
+            Intersection::Point {
+                point: whole([2, 2, 2]),
+                t: Ratio::from(2_i64),
+                u: Ratio::from(2_i64)
+            }
+        );
+        // skew
+        let c = Line3::line([2, 0, 3], [0, 1, 0]);
+        assert_eq!(a.intersect(&c), Intersection::None);
+    }
+}
@@ -0,0 +1,151 @@
+//! Closed polygons with integer vertices, and counting the lattice points inside them.
+//!
+//! Everything that can be is kept exact: areas come back doubled so they're always integers.
+
+use crate::math::gcd;
+
+pub type Point = (i64, i64);
+
+/// A closed polygon, the last vertex joins back up to the first. Assumed not to cross itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    /// Closes the polygon automatically, so repeating the first vertex at the end is optional
+    pub fn from_vertices<I: IntoIterator<Item = Point>>(vertices: I) -> Self {
+        let mut vertices: Vec<Point> = vertices.into_iter().collect();
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Self { vertices }
+    }
+
+    /// Walks from `start`, each move being a direction (usually a unit step like `(0, -1)`) and
+    /// how many times to take it, e.g. dig plan instructions. The walk should end back at
+    /// `start`.
+    pub fn from_moves<I: IntoIterator<Item = (Point, i64)>>(start: Point, moves: I) -> Self {
+        let mut pos = start;
+        let mut vertices = vec![start];
+        for ((dx, dy), length) in moves {
+            pos = (pos.0 + dx * length, pos.1 + dy * length);
+            vertices.push(pos);
+        }
+        Self::from_vertices(vertices)
+    }
+
+    #[must_use]
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Each edge as `(from, to)`, including the closing one
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+        self.vertices.iter().copied().zip(next.copied())
+    }
+
+    /// Twice the signed area (shoelace formula), positive if the vertices go anticlockwise with y
+    /// up (so clockwise on a y-down grid)
+    #[must_use]
+    pub fn signed_double_area(&self) -> i64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| x1 * y2 - x2 * y1)
+            .sum()
+    }
+
+    /// Twice the area, always an integer for lattice polygons
+    #[must_use]
+    pub fn double_area(&self) -> i64 {
+        self.signed_double_area().abs()
+    }
+
+    /// Euclidean length of the boundary
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| ((x2 - x1) as f64).hypot((y2 - y1) as f64))
+            .sum()
+    }
+
+    /// Lattice points on the boundary, which is just the perimeter if every edge is axis
+    /// aligned
+    #[must_use]
+    pub fn boundary_points(&self) -> i64 {
+        self.edges()
+            .map(|((x1, y1), (x2, y2))| gcd(x2 - x1, y2 - y1))
+            .sum()
+    }
+
+    /// Lattice points strictly inside, by Pick's theorem (`A = i + b/2 - 1`)
+    #[must_use]
+    pub fn interior_points(&self) -> i64 {
+        if self.vertices.len() < 3 {
+            return 0;
+        }
+        // 2A and b always have the same parity, so this divides exactly
+        (self.double_area() - self.boundary_points()) / 2 + 1
+    }
+
+    /// Lattice points inside or on the boundary, i.e. how many grid cells a loop of cells
+    /// covers when the vertices are cell centres
+    #[must_use]
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square() {
+        let square = Polygon::from_vertices([(0, 0), (4, 0), (4, 4), (0, 4), (0, 0)]);
+        assert_eq!(square.vertices().len(), 4);
+        assert_eq!(square.signed_double_area(), 32);
+        assert!((square.perimeter() - 16.0).abs() < f64::EPSILON);
+        assert_eq!(square.boundary_points(), 16);
+        assert_eq!(square.interior_points(), 9);
+        assert_eq!(square.lattice_points(), 25);
+        let reversed = Polygon::from_vertices(square.vertices().iter().rev().copied());
+        assert_eq!(reversed.signed_double_area(), -32);
+        assert_eq!(reversed.interior_points(), 9);
+    }
+
+    #[test]
+    fn test_triangle_diagonal_edges() {
+        let triangle = Polygon::from_vertices([(0, 0), (4, 0), (0, 2)]);
+        assert_eq!(triangle.double_area(), 8);
+        // 4 along the bottom, 2 up the side, gcd(4, 2) on the slope
+        assert_eq!(triangle.boundary_points(), 8);
+        assert_eq!(triangle.interior_points(), 1);
+    }
+
+    #[test]
+    fn test_dig_plan_example() {
+        // 2023 day 18 example, part 1
+        let (right, down, left, up) = ((1, 0), (0, 1), (-1, 0), (0, -1));
+        let moves = [
+            (right, 6),
+            (down, 5),
+            (left, 2),
+            (down, 2),
+            (right, 2),
+            (down, 2),
+            (left, 5),
+            (up, 2),
+            (left, 1),
+            (up, 2),
+            (right, 2),
+            (up, 3),
+            (left, 2),
+            (up, 2),
+        ];
+        let lagoon = Polygon::from_moves((0, 0), moves);
+        assert_eq!(lagoon.boundary_points(), 38);
+        assert_eq!(lagoon.lattice_points(), 62);
+    }
+}
@@ -0,0 +1,519 @@
+//! Explicit graphs, for puzzles where the input is a list of nodes and edges rather than a grid.
+//!
+//! Node labels (usually `&str`s borrowed from the input) are interned to [`NodeId`]s, which are
+//! just indices, so the algorithms can work on `Vec`s rather than hashing labels everywhere.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+
+use crate::math::dfs;
+
+mod flow;
+
+pub use flow::Cut;
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    directed: bool,
+    labels: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    adjacency: Vec<Vec<(NodeId, i64)>>,
+}
+
+impl<N: Eq + Hash + Clone> Graph<N> {
+    #[must_use]
+    pub fn directed() -> Self {
+        Self {
+            directed: true,
+            labels: vec![],
+            ids: HashMap::new(),
+            adjacency: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn undirected() -> Self {
+        Self {
+            directed: false,
+            ..Self::directed()
+        }
+    }
+
+    /// e.g. `adj.iter().map(|(&from, &lr)| (from, <[_; 2]>::from(lr)))` for day 8's
+    /// `HashMap<&str, (&str, &str)>`
+    pub fn directed_from_adjacency<I, J>(adjacency: I) -> Self
+    where
+        I: IntoIterator<Item = (N, J)>,
+        J: IntoIterator<Item = N>,
+    {
+        let mut graph = Self::directed();
+        for (from, tos) in adjacency {
+            graph.node(from.clone());
+            for to in tos {
+                graph.add_edge(from.clone(), to);
+            }
+        }
+        graph
+    }
+
+    /// e.g. day 25's `abc: def ghi` lines, split into `("abc", ["def", "ghi"])`
+    pub fn undirected_from_adjacency<I, J>(adjacency: I) -> Self
+    where
+        I: IntoIterator<Item = (N, J)>,
+        J: IntoIterator<Item = N>,
+    {
+        let mut graph = Self::undirected();
+        for (from, tos) in adjacency {
+            graph.node(from.clone());
+            for to in tos {
+                graph.add_edge(from.clone(), to);
+            }
+        }
+        graph
+    }
+
+    pub fn undirected_from_edges<I: IntoIterator<Item = (N, N)>>(edges: I) -> Self {
+        let mut graph = Self::undirected();
+        graph.extend(edges);
+        graph
+    }
+
+    #[must_use]
+    pub const fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// The id for `label`, adding it as a new node if it isn't one already
+    pub fn node(&mut self, label: N) -> NodeId {
+        if let Some(&id) = self.ids.get(&label) {
+            return id;
+        }
+        let id = self.labels.len();
+        self.ids.insert(label.clone(), id);
+        self.labels.push(label);
+        self.adjacency.push(vec![]);
+        id
+    }
+
+    #[must_use]
+    pub fn id(&self, label: &N) -> Option<NodeId> {
+        self.ids.get(label).copied()
+    }
+
+    #[must_use]
+    pub fn label(&self, id: NodeId) -> &N {
+        &self.labels[id]
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.add_weighted_edge(from, to, 1);
+    }
+
+    pub fn add_weighted_edge(&mut self, from: N, to: N, weight: i64) {
+        let from = self.node(from);
+        let to = self.node(to);
+        self.add_edge_by_id(from, to, weight);
+    }
+
+    pub fn add_edge_by_id(&mut self, from: NodeId, to: NodeId, weight: i64) {
+        self.adjacency[from].push((to, weight));
+        if !self.directed && from != to {
+            self.adjacency[to].push((from, weight));
+        }
+    }
+
+    /// Outgoing edges and their weights (all edges, for undirected graphs)
+    #[must_use]
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, i64)] {
+        &self.adjacency[id]
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.adjacency[id].iter().map(|&(to, _)| to)
+    }
+
+    #[must_use]
+    pub const fn ids(&self) -> std::ops::Range<NodeId> {
+        0..self.len()
+    }
+
+    fn reversed_adjacency(&self) -> Vec<Vec<NodeId>> {
+        let mut reversed = vec![vec![]; self.len()];
+        for from in self.ids() {
+            for to in self.neighbors(from) {
+                reversed[to].push(from);
+            }
+        }
+        reversed
+    }
+
+    /// Every node ordered so edges only point forwards, or `None` if there's a cycle. Only
+    /// meaningful for directed graphs.
+    #[must_use]
+    pub fn topological_sort(&self) -> Option<Vec<NodeId>> {
+        let mut in_degree = vec![0; self.len()];
+        for to in self.ids().flat_map(|from| self.neighbors(from)) {
+            in_degree[to] += 1;
+        }
+        let mut ready: VecDeque<_> = self.ids().filter(|&id| in_degree[id] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for to in self.neighbors(id) {
+                in_degree[to] -= 1;
+                if in_degree[to] == 0 {
+                    ready.push_back(to);
+                }
+            }
+        }
+        (order.len() == self.len()).then_some(order)
+    }
+
+    /// Kosaraju's algorithm. Components come out in topological order of the condensed graph
+    /// (so a component only has edges to components after it).
+    #[must_use]
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut visited = vec![false; self.len()];
+        let mut finish_order = Vec::with_capacity(self.len());
+        for root in self.ids() {
+            if visited[root] {
+                continue;
+            }
+            dfs(
+                &root,
+                |&id| self.neighbors(id).collect::<Vec<_>>(),
+                |&id| !std::mem::replace(&mut visited[id], true),
+                |&id| finish_order.push(id),
+            );
+        }
+
+        let reversed = self.reversed_adjacency();
+        let mut assigned = vec![false; self.len()];
+        let mut components = vec![];
+        for &root in finish_order.iter().rev() {
+            if assigned[root] {
+                continue;
+            }
+            let mut component = vec![];
+            dfs(
+                &root,
+                |&id| reversed[id].clone(),
+                |&id| !std::mem::replace(&mut assigned[id], true),
+                |&id| component.push(id),
+            );
+            components.push(component);
+        }
+        components
+    }
+
+    #[must_use]
+    pub fn has_cycle(&self) -> bool {
+        self.find_cycle().is_some()
+    }
+
+    /// Some cycle in the graph as a list of nodes, where the last node has an edge back to the
+    /// first. For undirected graphs, going back along the same edge doesn't count.
+    #[must_use]
+    pub fn find_cycle(&self) -> Option<Vec<NodeId>> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum State {
+            Unseen,
+            OnStack,
+            Done,
+        }
+        let mut state = vec![State::Unseen; self.len()];
+        let mut parent: Vec<Option<NodeId>> = vec![None; self.len()];
+        for root in self.ids() {
+            if state[root] != State::Unseen {
+                continue;
+            }
+            state[root] = State::OnStack;
+            // (node, next edge to look at, whether we've skipped the edge back to the parent)
+            let mut stack = vec![(root, 0, false)];
+            while let Some((id, next_edge, skipped_parent)) = stack.last_mut() {
+                let Some(&(to, _)) = self.adjacency[*id].get(*next_edge) else {
+                    state[*id] = State::Done;
+                    stack.pop();
+                    continue;
+                };
+                *next_edge += 1;
+                if !self.directed && Some(to) == parent[*id] && !*skipped_parent {
+                    *skipped_parent = true;
+                    continue;
+                }
+                match state[to] {
+                    State::Unseen => {
+                        parent[to] = Some(*id);
+                        state[to] = State::OnStack;
+                        stack.push((to, 0, false));
+                    }
+                    State::OnStack => {
+                        let mut cycle = vec![*id];
+                        let mut current = *id;
+                        while current != to {
+                            current = parent[current]?;
+                            cycle.push(current);
+                        }
+                        cycle.reverse();
+                        return Some(cycle);
+                    }
+                    State::Done => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// Bridges (edges whose removal disconnects the graph) and articulation points (nodes whose
+    /// removal does), via Tarjan's lowlink. Only meaningful for undirected graphs.
+    #[must_use]
+    pub fn bridges_and_articulation_points(&self) -> (Vec<(NodeId, NodeId)>, Vec<NodeId>) {
+        let mut discovered: Vec<Option<usize>> = vec![None; self.len()];
+        let mut low = vec![0; self.len()];
+        let mut timer = 0;
+        let mut bridges = vec![];
+        let mut articulation_points = HashSet::new();
+        for root in self.ids() {
+            if discovered[root].is_some() {
+                continue;
+            }
+            discovered[root] = Some(timer);
+            low[root] = timer;
+            timer += 1;
+            let mut root_children = 0;
+            // (node, parent, next edge to look at, whether we've skipped the edge to the parent)
+            let mut stack = vec![(root, None, 0, false)];
+            while let Some((id, parent, next_edge, skipped_parent)) = stack.last_mut() {
+                let id = *id;
+                if let Some(&(to, _)) = self.adjacency[id].get(*next_edge) {
+                    *next_edge += 1;
+                    if Some(to) == *parent && !*skipped_parent {
+                        // parallel edges back to the parent still count
+                        *skipped_parent = true;
+                        continue;
+                    }
+                    if let Some(to_discovered) = discovered[to] {
+                        low[id] = low[id].min(to_discovered);
+                    } else {
+                        discovered[to] = Some(timer);
+                        low[to] = timer;
+                        timer += 1;
+                        if id == root {
+                            root_children += 1;
+                        }
+                        stack.push((to, Some(id), 0, false));
+                    }
+                    continue;
+                }
+                let parent = *parent;
+                stack.pop();
+                let Some(parent) = parent else {
+                    continue;
+                };
+                low[parent] = low[parent].min(low[id]);
+                let parent_discovered = discovered[parent].unwrap_or_default();
+                if low[id] > parent_discovered {
+                    bridges.push((parent, id));
+                }
+                if parent != root && low[id] >= parent_discovered {
+                    articulation_points.insert(parent);
+                }
+            }
+            if root_children > 1 {
+                articulation_points.insert(root);
+            }
+        }
+        let mut articulation_points: Vec<_> = articulation_points.into_iter().collect();
+        articulation_points.sort_unstable();
+        (bridges, articulation_points)
+    }
+
+    #[must_use]
+    pub fn bridges(&self) -> Vec<(NodeId, NodeId)> {
+        self.bridges_and_articulation_points().0
+    }
+
+    #[must_use]
+    pub fn articulation_points(&self) -> Vec<NodeId> {
+        self.bridges_and_articulation_points().1
+    }
+
+    /// Shortest distance between every pair of nodes, indexed `[from][to]`, `None` where there's
+    /// no path. Handles negative weights, but not negative cycles.
+    #[allow(clippy::needless_range_loop)]
+    #[must_use]
+    pub fn floyd_warshall(&self) -> Vec<Vec<Option<i64>>> {
+        let n = self.len();
+        let mut distances = vec![vec![None; n]; n];
+        for from in self.ids() {
+            distances[from][from] = Some(0);
+            for &(to, weight) in self.edges(from) {
+                let existing = distances[from][to].get_or_insert(weight);
+                *existing = (*existing).min(weight);
+            }
+        }
+        for via in 0..n {
+            for from in 0..n {
+                let Some(to_via) = distances[from][via] else {
+                    continue;
+                };
+                for to in 0..n {
+                    if let Some(from_via) = distances[via][to] {
+                        let through = to_via + from_via;
+                        if distances[from][to].is_none_or(|direct| through < direct) {
+                            distances[from][to] = Some(through);
+                        }
+                    }
+                }
+            }
+        }
+        distances
+    }
+}
+
+/// Directed edges
+impl<N: Eq + Hash + Clone> FromIterator<(N, N)> for Graph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(iter: I) -> Self {
+        let mut graph = Self::directed();
+        graph.extend(iter);
+        graph
+    }
+}
+
+impl<N: Eq + Hash + Clone> Extend<(N, N)> for Graph<N> {
+    fn extend<I: IntoIterator<Item = (N, N)>>(&mut self, iter: I) {
+        for (from, to) in iter {
+            self.add_edge(from, to);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels<'a>(graph: &Graph<&'a str>, ids: &[NodeId]) -> Vec<&'a str> {
+        ids.iter().map(|&id| *graph.label(id)).collect()
+    }
+
+    #[test]
+    fn test_from_day8_adjacency() {
+        let adj: HashMap<&str, (&str, &str)> = [
+            ("AAA", ("BBB", "CCC")),
+            ("BBB", ("DDD", "EEE")),
+            ("CCC", ("ZZZ", "GGG")),
+        ]
+        .into_iter()
+        .collect();
+        let graph =
+            Graph::directed_from_adjacency(adj.iter().map(|(&k, &lr)| (k, <[_; 2]>::from(lr))));
+        assert_eq!(graph.len(), 7);
+        let aaa = graph.id(&"AAA").unwrap();
+        assert_eq!(
+            labels(&graph, &graph.neighbors(aaa).collect::<Vec<_>>()),
+            ["BBB", "CCC"]
+        );
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(order[0], aaa);
+    }
+
+    #[test]
+    fn test_topological_sort_and_cycles() {
+        let graph: Graph<_> = [("a", "b"), ("b", "c"), ("a", "c")].into_iter().collect();
+        assert_eq!(
+            labels(&graph, &graph.topological_sort().unwrap()),
+            ["a", "b", "c"]
+        );
+        assert!(!graph.has_cycle());
+
+        let graph: Graph<_> = [("a", "b"), ("b", "c"), ("c", "b")].into_iter().collect();
+        assert!(graph.topological_sort().is_none());
+        assert_eq!(labels(&graph, &graph.find_cycle().unwrap()), ["b", "c"]);
+    }
+
+    #[test]
+    fn test_undirected_cycles() {
+        let tree = Graph::undirected_from_edges([("a", "b"), ("b", "c"), ("b", "d")]);
+        assert!(!tree.has_cycle());
+        let cyclic = Graph::undirected_from_edges([("a", "b"), ("b", "c"), ("c", "a")]);
+        assert_eq!(cyclic.find_cycle().map(|c| c.len()), Some(3));
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let graph: Graph<_> = [
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "d"),
+        ]
+        .into_iter()
+        .collect();
+        let components: Vec<Vec<_>> = graph
+            .strongly_connected_components()
+            .iter()
+            .map(|component| {
+                let mut component = labels(&graph, component);
+                component.sort_unstable();
+                component
+            })
+            .collect();
+        assert_eq!(components, [vec!["a", "b", "c"], vec!["d", "e"]]);
+    }
+
+    #[test]
+    fn test_bridges_and_articulation_points() {
+        // two triangles joined by the c-d edge
+        let graph = Graph::undirected_from_edges([
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("c", "d"),
+            ("d", "e"),
+            ("e", "f"),
+            ("f", "d"),
+        ]);
+        let bridges: Vec<_> = graph
+            .bridges()
+            .into_iter()
+            .map(|(a, b)| (*graph.label(a), *graph.label(b)))
+            .collect();
+        assert_eq!(bridges, [("c", "d")]);
+        assert_eq!(labels(&graph, &graph.articulation_points()), ["c", "d"]);
+
+        // a doubled edge isn't a bridge
+        let graph = Graph::undirected_from_edges([("a", "b"), ("a", "b")]);
+        assert!(graph.bridges().is_empty());
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        let mut graph = Graph::directed();
+        graph.add_weighted_edge("a", "b", 4);
+        graph.add_weighted_edge("a", "c", 1);
+        graph.add_weighted_edge("c", "b", 2);
+        graph.add_weighted_edge("b", "d", -1);
+        let distances = graph.floyd_warshall();
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|l| graph.id(&l).unwrap());
+        assert_eq!(distances[a][b], Some(3));
+        assert_eq!(distances[a][d], Some(2));
+        assert_eq!(distances[c][d], Some(1));
+        assert_eq!(distances[d][a], None);
+    }
+}
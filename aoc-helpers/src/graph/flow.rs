@@ -0,0 +1,295 @@
+//! Max flow and min cuts, using edge weights as capacities.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use hashbrown::HashMap;
+use priority_queue::PriorityQueue;
+
+use super::{Graph, NodeId};
+
+/// A partition of a graph's nodes into two sides, and the edges between them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cut {
+    /// Total capacity of the cut edges
+    pub weight: i64,
+    /// Edges going from `source_side` to `sink_side`
+    pub edges: Vec<(NodeId, NodeId)>,
+    pub source_side: Vec<NodeId>,
+    pub sink_side: Vec<NodeId>,
+}
+
+impl Cut {
+    #[must_use]
+    pub const fn sizes(&self) -> (usize, usize) {
+        (self.source_side.len(), self.sink_side.len())
+    }
+}
+
+/// Residual network with each edge stored next to its reverse, so `edge ^ 1` is the reverse
+struct Residual {
+    adjacency: Vec<Vec<usize>>,
+    to: Vec<NodeId>,
+    capacity: Vec<i64>,
+}
+
+impl Residual {
+    fn new<N: Eq + Hash + Clone>(graph: &Graph<N>) -> Self {
+        let mut residual = Self {
+            adjacency: vec![vec![]; graph.len()],
+            to: vec![],
+            capacity: vec![],
+        };
+        for from in graph.ids() {
+            for &(to, weight) in graph.edges(from) {
+                // undirected edges are already stored in both directions, and the reverse of a
+                // directed edge starts out empty
+                residual.add(from, to, weight);
+            }
+        }
+        residual
+    }
+
+    fn add(&mut self, from: NodeId, to: NodeId, capacity: i64) {
+        self.adjacency[from].push(self.to.len());
+        self.to.push(to);
+        self.capacity.push(capacity);
+        self.adjacency[to].push(self.to.len());
+        self.to.push(from);
+        self.capacity.push(0);
+    }
+
+    /// Edges used to reach each node on a shortest augmenting path, if `sink` is reachable
+    fn augmenting_path(&self, source: NodeId, sink: NodeId) -> Option<Vec<usize>> {
+        let mut via: Vec<Option<usize>> = vec![None; self.adjacency.len()];
+        let mut seen = vec![false; self.adjacency.len()];
+        seen[source] = true;
+        let mut to_visit = VecDeque::from([source]);
+        while let Some(node) = to_visit.pop_front() {
+            for &edge in &self.adjacency[node] {
+                let to = self.to[edge];
+                if !seen[to] && self.capacity[edge] > 0 {
+                    seen[to] = true;
+                    via[to] = Some(edge);
+                    to_visit.push_back(to);
+                }
+            }
+        }
+        if !seen[sink] {
+            return None;
+        }
+        let mut path = vec![];
+        let mut node = sink;
+        while let Some(edge) = via[node] {
+            path.push(edge);
+            node = self.to[edge ^ 1];
+        }
+        Some(path)
+    }
+
+    fn reachable_from(&self, source: NodeId) -> Vec<bool> {
+        let mut seen = vec![false; self.adjacency.len()];
+        seen[source] = true;
+        let mut to_visit = vec![source];
+        while let Some(node) = to_visit.pop() {
+            for &edge in &self.adjacency[node] {
+                let to = self.to[edge];
+                if !seen[to] && self.capacity[edge] > 0 {
+                    seen[to] = true;
+                    to_visit.push(to);
+                }
+            }
+        }
+        seen
+    }
+}
+
+impl<N: Eq + Hash + Clone> Graph<N> {
+    /// Edmonds-Karp. Returns the max flow along with the residual network it leaves behind.
+    fn saturate(&self, source: NodeId, sink: NodeId) -> (i64, Residual) {
+        let mut residual = Residual::new(self);
+        let mut flow = 0;
+        if source == sink {
+            return (flow, residual);
+        }
+        while let Some(path) = residual.augmenting_path(source, sink) {
+            let Some(bottleneck) = path.iter().map(|&edge| residual.capacity[edge]).min() else {
+                break;
+            };
+            for &edge in &path {
+                residual.capacity[edge] -= bottleneck;
+                residual.capacity[edge ^ 1] += bottleneck;
+            }
+            flow += bottleneck;
+        }
+        (flow, residual)
+    }
+
+    #[must_use]
+    pub fn max_flow(&self, source: NodeId, sink: NodeId) -> i64 {
+        self.saturate(source, sink).0
+    }
+
+    /// The cheapest set of edges separating `source` from `sink`. The source side is everything
+    /// still reachable from `source` once the flow is maxed out.
+    #[must_use]
+    pub fn min_cut(&self, source: NodeId, sink: NodeId) -> Cut {
+        let (weight, residual) = self.saturate(source, sink);
+        self.cut_from_sides(weight, &residual.reachable_from(source))
+    }
+
+    fn cut_from_sides(&self, weight: i64, on_source_side: &[bool]) -> Cut {
+        let (source_side, sink_side) = self.ids().partition(|&id| on_source_side[id]);
+        let edges = self
+            .ids()
+            .filter(|&from| on_source_side[from])
+            .flat_map(|from| {
+                self.neighbors(from)
+                    .filter(|&to| !on_source_side[to])
+                    .map(move |to| (from, to))
+            })
+            .collect();
+        Cut {
+            weight,
+            edges,
+            source_side,
+            sink_side,
+        }
+    }
+
+    /// Stoer-Wagner: the cheapest set of edges that splits the graph in two, or `None` if there
+    /// are fewer than two nodes. Only meaningful for undirected graphs.
+    #[must_use]
+    pub fn global_min_cut(&self) -> Option<Cut> {
+        if self.len() < 2 {
+            return None;
+        }
+        // nodes get merged together as we go, each remaining one stands for all of its members
+        let mut weights: Vec<HashMap<NodeId, i64>> = vec![HashMap::new(); self.len()];
+        for from in self.ids() {
+            for &(to, weight) in self.edges(from) {
+                if from != to {
+                    *weights[from].entry(to).or_default() += weight;
+                }
+            }
+        }
+        let mut members: Vec<Vec<NodeId>> = self.ids().map(|id| vec![id]).collect();
+        let mut active: Vec<NodeId> = self.ids().collect();
+        let mut best: Option<(i64, Vec<NodeId>)> = None;
+
+        while active.len() > 1 {
+            // maximum adjacency ordering, the last two added are the ones to merge
+            let mut queue: PriorityQueue<NodeId, i64> = active.iter().map(|&id| (id, 0)).collect();
+            let mut previous = None;
+            let mut last = None;
+            while let Some((node, connectivity)) = queue.pop() {
+                for (&neighbor, &weight) in &weights[node] {
+                    queue.change_priority_by(&neighbor, |p| *p += weight);
+                }
+                previous = last.map(|(node, _)| node);
+                last = Some((node, connectivity));
+            }
+            let (Some(s), Some((t, cut_of_phase))) = (previous, last) else {
+                break;
+            };
+            if best
+                .as_ref()
+                .is_none_or(|(weight, _)| cut_of_phase < *weight)
+            {
+                best = Some((cut_of_phase, members[t].clone()));
+            }
+            // merge t into s
+            let t_members = std::mem::take(&mut members[t]);
+            members[s].extend(t_members);
+            let t_weights = std::mem::take(&mut weights[t]);
+            for (neighbor, weight) in t_weights {
+                weights[neighbor].remove(&t);
+                if neighbor != s {
+                    *weights[s].entry(neighbor).or_default() += weight;
+                    *weights[neighbor].entry(s).or_default() += weight;
+                }
+            }
+            active.retain(|&id| id != t);
+        }
+
+        let (weight, side) = best?;
+        let mut on_source_side = vec![false; self.len()];
+        for id in side {
+            on_source_side[id] = true;
+        }
+        Some(self.cut_from_sides(weight, &on_source_side))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_flow() {
+        let mut graph = Graph::directed();
+        for (from, to, capacity) in [
+            ("s", "a", 10),
+            ("s", "b", 5),
+            ("a", "b", 15),
+            ("a", "t", 5),
+            ("b", "t", 10),
+        ] {
+            graph.add_weighted_edge(from, to, capacity);
+        }
+        let [s, a, b, t] = ["s", "a", "b", "t"].map(|l| graph.id(&l).unwrap());
+        assert_eq!(graph.max_flow(s, t), 15);
+        let cut = graph.min_cut(s, t);
+        assert_eq!(cut.weight, 15);
+        // {s, a, b} | {t} is just as cheap, but the source side is kept as small as possible
+        assert_eq!(cut.sizes(), (1, 3));
+        let mut edges = cut.edges;
+        edges.sort_unstable();
+        assert_eq!(edges, [(s, a), (s, b)]);
+        assert_eq!(graph.max_flow(t, s), 0);
+    }
+
+    #[test]
+    fn test_global_min_cut_wires() {
+        // three wires hold two clusters together
+        let lines = [
+            "jqt: rhn xhk nvd",
+            "rsh: frs pzl lsr",
+            "xhk: hfx",
+            "cmg: qnr nvd lhk bvb",
+            "rhn: xhk bvb hfx",
+            "bvb: xhk hfx",
+            "pzl: lsr hfx nvd",
+            "qnr: nvd",
+            "ntq: jqt hfx bvb xhk",
+            "nvd: lhk",
+            "lsr: lhk",
+            "rzs: qnr cmg lsr rsh",
+            "frs: qnr lhk lsr",
+        ];
+        let graph = Graph::undirected_from_adjacency(lines.iter().map(|line| {
+            let (from, tos) = line.split_once(": ").unwrap();
+            (from, tos.split_whitespace())
+        }));
+        let cut = graph.global_min_cut().unwrap();
+        assert_eq!(cut.weight, 3);
+        let (a, b) = cut.sizes();
+        assert_eq!(a * b, 54);
+        let mut edges: Vec<_> = cut
+            .edges
+            .iter()
+            .map(|&(a, b)| {
+                let mut edge = [*graph.label(a), *graph.label(b)];
+                edge.sort_unstable();
+                edge
+            })
+            .collect();
+        edges.sort_unstable();
+        assert_eq!(edges, [["bvb", "cmg"], ["hfx", "pzl"], ["jqt", "nvd"]]);
+
+        // the source/sink version agrees
+        let s = graph.id(&"jqt").unwrap();
+        let t = graph.id(&"nvd").unwrap();
+        assert_eq!(graph.min_cut(s, t).weight, 3);
+    }
+}
@@ -0,0 +1,229 @@
+//! Axis-aligned boxes in `N` dimensions, for chopping up a space of possible values by
+//! threshold conditions (and for counting how much of it is left).
+//!
+//! Like [`crate::ranges`], every axis is a half open `i64` range.
+
+use std::ops::Range;
+
+/// A box with one half open range per axis
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hyperrect<const N: usize> {
+    pub axes: [Range<i64>; N],
+}
+
+impl<const N: usize> Hyperrect<N> {
+    #[must_use]
+    pub const fn new(axes: [Range<i64>; N]) -> Self {
+        Self { axes }
+    }
+
+    /// The same range on every axis, e.g. `1..4001` for all of x, m, a and s
+    #[must_use]
+    pub fn cube(range: Range<i64>) -> Self {
+        Self {
+            axes: std::array::from_fn(|_| range.clone()),
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.axes.iter().any(Range::is_empty)
+    }
+
+    /// Number of integer points inside
+    #[must_use]
+    pub fn volume(&self) -> i64 {
+        if self.is_empty() {
+            return 0;
+        }
+        self.axes.iter().map(|axis| axis.end - axis.start).product()
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.axes
+            .iter()
+            .zip(point)
+            .all(|(axis, p)| axis.contains(p))
+    }
+
+    /// The overlap of the two boxes, `None` if they don't overlap
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let overlap = Self {
+            axes: std::array::from_fn(|i| {
+                self.axes[i].start.max(other.axes[i].start)..self.axes[i].end.min(other.axes[i].end)
+            }),
+        };
+        (!overlap.is_empty()).then_some(overlap)
+    }
+
+    /// The part with `axis` below `at`, and the part with `axis` from `at` up. Either is `None`
+    /// if it would be empty.
+    ///
+    /// # Panics
+    ///
+    /// If `axis >= N`
+    #[must_use]
+    pub fn split(&self, axis: usize, at: i64) -> (Option<Self>, Option<Self>) {
+        let range = &self.axes[axis];
+        let mut below = self.clone();
+        below.axes[axis] = range.start..at.min(range.end);
+        let mut above = self.clone();
+        above.axes[axis] = at.max(range.start)..range.end;
+        (
+            (!below.is_empty()).then_some(below),
+            (!above.is_empty()).then_some(above),
+        )
+    }
+
+    /// `self` with `other` cut out, as up to `2 * N` disjoint boxes
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        if self.intersection(other).is_none() {
+            return vec![self.clone()];
+        }
+        // peel off the slabs either side of other one axis at a time, what's left at the end is
+        // the overlap
+        let mut pieces = vec![];
+        let mut rest = self.clone();
+        for axis in 0..N {
+            let (below, middle) = rest.split(axis, other.axes[axis].start);
+            pieces.extend(below);
+            let Some(middle) = middle else {
+                break;
+            };
+            let (middle, above) = middle.split(axis, other.axes[axis].end);
+            pieces.extend(above);
+            let Some(middle) = middle else {
+                break;
+            };
+            rest = middle;
+        }
+        pieces
+    }
+}
+
+/// A union of boxes, stored as disjoint [`Hyperrect`]s so the volume is just a sum
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Region<const N: usize> {
+    rects: Vec<Hyperrect<N>>,
+}
+
+impl<const N: usize> Region<N> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { rects: vec![] }
+    }
+
+    /// Disjoint boxes making up the region, in no particular order
+    #[must_use]
+    pub fn rects(&self) -> &[Hyperrect<N>] {
+        &self.rects
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    #[must_use]
+    pub fn volume(&self) -> i64 {
+        self.rects.iter().map(Hyperrect::volume).sum()
+    }
+
+    #[must_use]
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.rects.iter().any(|rect| rect.contains(point))
+    }
+
+    pub fn insert(&mut self, rect: Hyperrect<N>) {
+        // only add the bits that aren't already covered
+        let mut new_pieces = vec![rect];
+        for existing in &self.rects {
+            new_pieces = new_pieces
+                .iter()
+                .flat_map(|piece| piece.difference(existing))
+                .collect();
+        }
+        self.rects.extend(new_pieces);
+    }
+
+    pub fn remove(&mut self, rect: &Hyperrect<N>) {
+        self.rects = self
+            .rects
+            .iter()
+            .flat_map(|existing| existing.difference(rect))
+            .collect();
+    }
+
+    #[must_use]
+    pub fn intersection(&self, rect: &Hyperrect<N>) -> Self {
+        Self {
+            rects: self
+                .rects
+                .iter()
+                .filter_map(|existing| existing.intersection(rect))
+                .collect(),
+        }
+    }
+}
+
+impl<const N: usize> FromIterator<Hyperrect<N>> for Region<N> {
+    fn from_iter<I: IntoIterator<Item = Hyperrect<N>>>(iter: I) -> Self {
+        let mut region = Self::new();
+        for rect in iter {
+            region.insert(rect);
+        }
+        region
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_volume() {
+        let rect: Hyperrect<4> = Hyperrect::cube(1..4001);
+        assert_eq!(rect.volume(), 4000_i64.pow(4));
+        // x < 1416
+        let (matches, not_matches) = rect.split(0, 1416);
+        assert_eq!(matches.unwrap().volume(), 1415 * 4000_i64.pow(3));
+        assert_eq!(not_matches.unwrap().axes[0], 1416..4001);
+        assert_eq!(rect.split(1, 0), (None, Some(rect.clone())));
+        assert_eq!(rect.split(1, 5000), (Some(rect), None));
+    }
+
+    #[test]
+    fn test_intersection_and_difference() {
+        let a = Hyperrect::new([0..10, 0..10]);
+        let b = Hyperrect::new([5..15, 2..4]);
+        assert_eq!(a.intersection(&b), Some(Hyperrect::new([5..10, 2..4])));
+        assert_eq!(a.intersection(&Hyperrect::new([10..20, 0..10])), None);
+        let pieces = a.difference(&b);
+        assert_eq!(pieces.iter().map(Hyperrect::volume).sum::<i64>(), 90);
+        assert!(pieces.iter().all(|p| p.intersection(&b).is_none()));
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn test_region_union_volume() {
+        let region: Region<3> = [
+            Hyperrect::cube(0..3),
+            Hyperrect::cube(1..4),
+            Hyperrect::cube(1..2),
+        ]
+        .into_iter()
+        .collect();
+        // 27 + 27 - 8 overlap
+        assert_eq!(region.volume(), 46);
+        assert!(region.contains(&[3, 3, 3]));
+        assert!(!region.contains(&[0, 3, 3]));
+        assert_eq!(region.intersection(&Hyperrect::cube(2..10)).volume(), 8);
+
+        let mut region = region;
+        region.remove(&Hyperrect::cube(0..2));
+        assert_eq!(region.volume(), 38);
+    }
+}
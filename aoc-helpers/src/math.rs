@@ -0,0 +1,13 @@
+pub mod cycle;
+pub mod number_theory;
+pub mod search;
+pub mod shortest_paths;
+pub mod traversal;
+
+pub use cycle::{find_cycle, state_after, Cycle};
+pub use number_theory::{
+    checked_gcd, checked_lcm, crt, extended_gcd, gcd, lcm, mod_inverse, mod_pow, Integer,
+};
+pub use search::{all_pairs, astar, dijkstra, dijkstra_distances, nearest_goal, Cost, Search};
+pub use shortest_paths::{dijkstra_all, ShortestPaths};
+pub use traversal::{bfs, bfs_distances, bfs_distances_from, dfs, reachable};
@@ -0,0 +1,142 @@
+//! Finding where a repeatedly stepped state starts looping, for the "now do it a billion times"
+//! puzzles.
+
+use std::hash::Hash;
+
+use hashbrown::HashMap;
+
+/// The states seen while stepping from an initial state until one repeated.
+///
+/// `states[0]` is the initial state, and `states[start + length]` would be the same as
+/// `states[start]`.
+#[derive(Debug, Clone)]
+pub struct Cycle<T> {
+    /// Number of steps before the first state that's part of the loop
+    pub start: usize,
+    /// Number of steps to go around the loop once
+    pub length: usize,
+    states: Vec<T>,
+}
+
+impl<T> Cycle<T> {
+    /// The state after `steps` steps from the initial state
+    #[must_use]
+    pub fn state_at(&self, steps: usize) -> &T {
+        if steps < self.states.len() {
+            &self.states[steps]
+        } else {
+            &self.states[self.start + (steps - self.start) % self.length]
+        }
+    }
+
+    /// Every distinct state, in order
+    #[must_use]
+    pub fn states(&self) -> &[T] {
+        &self.states
+    }
+}
+
+/// Steps from `initial` until a state repeats, remembering every state along the way
+pub fn find_cycle<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    let mut seen = HashMap::new();
+    let mut states = vec![];
+    let mut current = initial;
+    loop {
+        if let Some(&start) = seen.get(&current) {
+            let length = states.len() - start;
+            return Cycle {
+                start,
+                length,
+                states,
+            };
+        }
+        let next = step(&current);
+        seen.insert(current.clone(), states.len());
+        states.push(current);
+        current = next;
+    }
+}
+
+/// The state after `steps` steps from `initial`, skipping round the loop once it's found
+pub fn state_after<T, F>(initial: T, step: F, steps: usize) -> T
+where
+    T: Hash + Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    find_cycle(initial, step).state_at(steps).clone()
+}
+
+/// Brent's algorithm: `(start, length)` like [`Cycle`], but only ever keeps a couple of states
+/// around (at the cost of stepping more), and doesn't need them to be hashable
+pub fn brent<T, F>(initial: &T, mut step: F) -> (usize, usize)
+where
+    T: Eq + Clone,
+    F: FnMut(&T) -> T,
+{
+    // find the length by racing the hare ahead in growing powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // then walk two states `length` apart from the start until they meet
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    (start, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 2 ...
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    const fn step(n: &u32) -> u32 {
+        if *n == 5 {
+            2
+        } else {
+            *n + 1
+        }
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let cycle = find_cycle(0, step);
+        assert_eq!((cycle.start, cycle.length), (2, 4));
+        assert_eq!(cycle.states(), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(*cycle.state_at(1), 1);
+        assert_eq!(*cycle.state_at(6), 2);
+        assert_eq!(*cycle.state_at(1_000_000_000), 2 + (1_000_000_000 - 2) % 4);
+        assert_eq!(state_after(0, step, 9), 5);
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(brent(&0, step), (2, 4));
+        assert_eq!(brent(&3, step), (0, 4));
+        // immediately loops back to itself
+        assert_eq!(brent(&7, |&n| n), (0, 1));
+    }
+}
@@ -0,0 +1,243 @@
+//! gcd/lcm and modular arithmetic, mostly for lining up cycles.
+//!
+//! The modular functions work on `i128` so the intermediate products of `i64` sized inputs can't
+//! overflow.
+
+use std::ops::{Div, Mul, Rem};
+
+/// The primitive integer types, so gcd and lcm can be generic
+pub trait Integer:
+    Copy + Ord + Div<Output = Self> + Mul<Output = Self> + Rem<Output = Self>
+{
+    const ZERO: Self;
+    /// `None` for `MIN` of a signed type, whose absolute value doesn't fit
+    fn checked_abs(self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    /// `%`, except `MIN % -1` is 0 rather than a panic
+    #[must_use]
+    fn wrapping_rem(self, other: Self) -> Self;
+}
+
+macro_rules! impl_integer {
+    (unsigned: $($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                fn checked_abs(self) -> Option<Self> {
+                    Some(self)
+                }
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+                fn wrapping_rem(self, other: Self) -> Self {
+                    <$t>::wrapping_rem(self, other)
+                }
+            }
+        )*
+    };
+    (signed: $($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: Self = 0;
+                fn checked_abs(self) -> Option<Self> {
+                    <$t>::checked_abs(self)
+                }
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+                fn wrapping_rem(self, other: Self) -> Self {
+                    <$t>::wrapping_rem(self, other)
+                }
+            }
+        )*
+    };
+}
+impl_integer!(unsigned: u8, u16, u32, u64, u128, usize);
+impl_integer!(signed: i8, i16, i32, i64, i128, isize);
+
+/// Always non-negative, and `gcd(n, 0) == n`
+///
+/// # Panics
+/// If the result doesn't fit in `T`, which is only when it's `-T::MIN` (e.g. `gcd(i64::MIN, 0)`),
+/// see [`checked_gcd`]
+pub fn gcd<T: Integer>(first: T, second: T) -> T {
+    checked_gcd(first, second).expect("gcd overflowed")
+}
+
+pub fn checked_gcd<T: Integer>(first: T, second: T) -> Option<T> {
+    let mut a = first;
+    let mut b = second;
+    while b != T::ZERO {
+        (a, b) = (b, a.wrapping_rem(b));
+    }
+    a.checked_abs()
+}
+
+/// Divides before multiplying, so only overflows if the result itself doesn't fit.
+///
+/// # Panics
+/// If the result doesn't fit in `T`, see [`checked_lcm`]
+pub fn lcm<T: Integer>(first: T, second: T) -> T {
+    checked_lcm(first, second).expect("lcm overflowed")
+}
+
+pub fn checked_lcm<T: Integer>(first: T, second: T) -> Option<T> {
+    if first == T::ZERO || second == T::ZERO {
+        return Some(T::ZERO);
+    }
+    (first / checked_gcd(first, second)?)
+        .checked_mul(second)
+        .and_then(Integer::checked_abs)
+}
+
+/// `(g, x, y)` such that `a * x + b * y == g == gcd(a, b)`
+#[must_use]
+#[allow(clippy::many_single_char_names)]
+pub const fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, old_x - quotient * x);
+        (old_y, y) = (y, old_y - quotient * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// `x` in `0..modulus` with `a * x ≡ 1 (mod modulus)`, if there is one (`a` and `modulus` need to
+/// be coprime, and `modulus` positive)
+#[must_use]
+pub const fn mod_inverse(a: i128, modulus: i128) -> Option<i128> {
+    if modulus <= 0 {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g == 1 {
+        Some(x.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+/// `base.pow(exp) % modulus`, by squaring
+///
+/// # Panics
+/// If `modulus` is 0
+#[must_use]
+pub const fn mod_pow(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    if modulus == 1 {
+        return 0;
+    }
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    // result < modulus, which came from a u64
+    #[allow(clippy::cast_possible_truncation)]
+    let result = result as u64;
+    result
+}
+
+/// Chinese remainder theorem: the smallest non-negative `x` with `x ≡ residue (mod modulus)` for
+/// every `(residue, modulus)` pair.
+///
+/// Also returns the lcm of the moduli, so every solution is `x + k * lcm`. The moduli don't need
+/// to be coprime, but then there might be no solution. `None` as well if a modulus isn't
+/// positive, or if the lcm (or a step on the way to it) doesn't fit in an `i128`.
+#[must_use]
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    let mut x: i128 = 0;
+    let mut modulus: i128 = 1;
+    for &(residue, next_modulus) in congruences {
+        if next_modulus <= 0 {
+            return None;
+        }
+        // x + modulus * k ≡ residue (mod next_modulus), solve for k
+        let (g, inverse, _) = extended_gcd(modulus, next_modulus);
+        let difference = residue.checked_sub(x)?;
+        if difference % g != 0 {
+            return None;
+        }
+        let reduced_modulus = next_modulus / g;
+        let k = (difference / g % reduced_modulus)
+            .checked_mul(inverse)?
+            .rem_euclid(reduced_modulus);
+        x = x.checked_add(modulus.checked_mul(k)?)?;
+        modulus = modulus.checked_mul(reduced_modulus)?;
+        x = x.rem_euclid(modulus);
+    }
+    Some((x, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12_u64, 18), 6);
+        assert_eq!(gcd(0_u64, 7), 7);
+        assert_eq!(gcd(7_u64, 0), 7);
+        assert_eq!(gcd(-12_i32, 18), 6);
+        assert_eq!(lcm(4_u64, 6), 12);
+        assert_eq!(lcm(0_u64, 6), 0);
+        assert_eq!(lcm(-4_i64, 6), 12);
+        // first * second would overflow, the lcm itself doesn't
+        assert_eq!(lcm(u64::MAX, u64::MAX), u64::MAX);
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        // -i64::MIN doesn't fit
+        assert_eq!(checked_gcd(i64::MIN, 0), None);
+        assert_eq!(checked_gcd(i64::MIN, -1), Some(1));
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(checked_lcm(i64::MIN, 1), None);
+    }
+
+    #[test]
+    fn test_extended_gcd_and_inverse() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 8), None);
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(mod_inverse(3, -11), None);
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        // Fermat's little theorem, with a prime big enough to overflow a u64 multiply
+        let prime = (1 << 61) - 1;
+        assert_eq!(mod_pow(u64::MAX, prime - 1, prime), 1);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // not coprime, but consistent
+        assert_eq!(crt(&[(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        // negative residues are fine
+        assert_eq!(crt(&[(-1, 5), (0, 3)]), Some((9, 15)));
+        // the lcm of the moduli doesn't fit
+        let big = (1 << 100) + 1;
+        assert_eq!(crt(&[(0, big), (1, big + 2)]), None);
+        assert_eq!(crt(&[(2, 3), (0, 0)]), None);
+        assert_eq!(crt(&[(2, -3)]), None);
+    }
+}
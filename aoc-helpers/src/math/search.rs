@@ -0,0 +1,455 @@
+//! Weighted shortest path searches over an implicit state space.
+//!
+//! States are anything hashable, and the graph is described by a `successors` closure giving the
+//! states reachable from a state along with the cost of getting there.
+
+use std::cmp::Reverse;
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+use priority_queue::PriorityQueue;
+
+/// Anything that can be used as the weight of an edge in a search: needs a zero, a way of adding,
+/// and a total ordering
+pub trait Cost: Ord + Clone {
+    fn zero() -> Self;
+    #[must_use]
+    fn add(&self, other: &Self) -> Self;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty),*) => {
+        $(
+            impl Cost for $t {
+                fn zero() -> Self {
+                    0
+                }
+                fn add(&self, other: &Self) -> Self {
+                    self + other
+                }
+            }
+        )*
+    };
+}
+impl_cost!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+// Compared lexicographically, added elementwise
+impl<A: Cost, B: Cost> Cost for (A, B) {
+    fn zero() -> Self {
+        (A::zero(), B::zero())
+    }
+    fn add(&self, other: &Self) -> Self {
+        (self.0.add(&other.0), self.1.add(&other.1))
+    }
+}
+impl<A: Cost, B: Cost, C: Cost> Cost for (A, B, C) {
+    fn zero() -> Self {
+        (A::zero(), B::zero(), C::zero())
+    }
+    fn add(&self, other: &Self) -> Self {
+        (
+            self.0.add(&other.0),
+            self.1.add(&other.1),
+            self.2.add(&other.2),
+        )
+    }
+}
+
+/// Builder for a best-first search from `start` (or several starts at once). Without a heuristic
+/// this is Dijkstra, with one it's A* (so the heuristic must never overestimate).
+///
+/// States are reopened if a cheaper way to them turns up later, so the heuristic doesn't have to be
+/// consistent, though it's faster if it is.
+///
+/// ```
+/// use aoc_helpers::math::Search;
+///
+/// // cheapest way to count up to 10 by +1 (cost 1) or *2 (cost 1)
+/// let (path, cost) = Search::new(1_u32, |&n| [(n + 1, 1_u32), (n * 2, 1)])
+///     .goal(|&n| n == 10)
+///     .max_cost(10)
+///     .run()
+///     .unwrap();
+/// assert_eq!(cost, 4);
+/// assert_eq!(path, vec![1, 2, 4, 5, 10]);
+/// ```
+pub struct Search<T, C, S, H = fn(&T) -> C, G = fn(&T) -> bool> {
+    starts: Vec<T>,
+    successors: S,
+    heuristic: H,
+    is_goal: G,
+    max_cost: Option<C>,
+    visit_limit: Option<usize>,
+}
+
+impl<T, C, S, I> Search<T, C, S>
+where
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+{
+    pub fn new(start: T, successors: S) -> Self {
+        Self::from_starts([start], successors)
+    }
+
+    /// Search from all of `starts` at once, as if they were all connected to a virtual start at
+    /// no cost
+    pub fn from_starts<It: IntoIterator<Item = T>>(starts: It, successors: S) -> Self {
+        Self {
+            starts: starts.into_iter().collect(),
+            successors,
+            heuristic: |_| C::zero(),
+            is_goal: |_| false,
+            max_cost: None,
+            visit_limit: None,
+        }
+    }
+}
+
+impl<T, C, S, H, G> Search<T, C, S, H, G> {
+    /// Estimate of the remaining cost to a goal, must never overestimate
+    pub fn heuristic<H2: FnMut(&T) -> C>(self, heuristic: H2) -> Search<T, C, S, H2, G> {
+        Search {
+            starts: self.starts,
+            successors: self.successors,
+            heuristic,
+            is_goal: self.is_goal,
+            max_cost: self.max_cost,
+            visit_limit: self.visit_limit,
+        }
+    }
+
+    pub fn goal<G2: FnMut(&T) -> bool>(self, is_goal: G2) -> Search<T, C, S, H, G2> {
+        Search {
+            starts: self.starts,
+            successors: self.successors,
+            heuristic: self.heuristic,
+            is_goal,
+            max_cost: self.max_cost,
+            visit_limit: self.visit_limit,
+        }
+    }
+
+    /// States costing more than this to reach are never explored
+    #[must_use]
+    pub fn max_cost(mut self, max_cost: C) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Give up after settling this many states
+    #[must_use]
+    pub const fn visit_limit(mut self, visit_limit: usize) -> Self {
+        self.visit_limit = Some(visit_limit);
+        self
+    }
+}
+
+struct Explored<T, C> {
+    came_from: HashMap<T, T>,
+    settled: HashMap<T, C>,
+    goal: Option<T>,
+}
+
+impl<T, C> Explored<T, C>
+where
+    T: Eq + Hash + Clone,
+    C: Clone,
+{
+    fn path_to(&self, goal: &T) -> Vec<T> {
+        let mut current = goal;
+        let mut path = vec![current.clone()];
+        while let Some(came) = self.came_from.get(current) {
+            path.push(came.clone());
+            current = came;
+        }
+        path.reverse();
+        path
+    }
+}
+
+impl<T, C, S, I, H, G> Search<T, C, S, H, G>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+    H: FnMut(&T) -> C,
+    G: FnMut(&T) -> bool,
+{
+    fn explore(&mut self) -> Explored<T, C> {
+        // PriorityQueue pops the max, so wrap in Reverse to get the cheapest
+        let mut to_visit = PriorityQueue::new();
+        let mut g_score = HashMap::new();
+        let mut explored = Explored {
+            came_from: HashMap::new(),
+            settled: HashMap::new(),
+            goal: None,
+        };
+        for start in &self.starts {
+            to_visit.push(start.clone(), Reverse((self.heuristic)(start)));
+            g_score.insert(start.clone(), C::zero());
+        }
+
+        while let Some((current, _fscore)) = to_visit.pop() {
+            if self
+                .visit_limit
+                .is_some_and(|limit| explored.settled.len() >= limit)
+            {
+                break;
+            }
+            let current_cost: C = g_score[&current].clone();
+            explored
+                .settled
+                .insert(current.clone(), current_cost.clone());
+            if (self.is_goal)(&current) {
+                explored.goal = Some(current);
+                break;
+            }
+            for (neighbor, weight) in (self.successors)(&current) {
+                let tentative_gscore = current_cost.add(&weight);
+                if self
+                    .max_cost
+                    .as_ref()
+                    .is_some_and(|max| &tentative_gscore > max)
+                {
+                    continue;
+                }
+                if g_score
+                    .get(&neighbor)
+                    .is_none_or(|old_score| &tentative_gscore < old_score)
+                {
+                    // only happens to a settled state when the heuristic isn't consistent, it
+                    // has to go back in the queue for the cheaper path to carry on from it
+                    explored.settled.remove(&neighbor);
+                    explored.came_from.insert(neighbor.clone(), current.clone());
+                    let h = (self.heuristic)(&neighbor);
+                    to_visit.push(neighbor.clone(), Reverse(tentative_gscore.add(&h)));
+                    g_score.insert(neighbor, tentative_gscore);
+                }
+            }
+        }
+        explored
+    }
+
+    /// The cheapest path from a start to a goal (inclusive of both) and its cost, or `None` if
+    /// no goal could be reached within the limits
+    pub fn run(mut self) -> Option<(Vec<T>, C)> {
+        let explored = self.explore();
+        let goal = explored.goal.as_ref()?;
+        Some((explored.path_to(goal), explored.settled[goal].clone()))
+    }
+
+    /// The cheapest cost to every state reached (stopping early at a goal, if there is one)
+    pub fn costs(mut self) -> HashMap<T, C> {
+        self.explore().settled
+    }
+}
+
+pub fn dijkstra<T, C, S, I, G>(start: &T, successors: S, is_goal: G) -> Option<(Vec<T>, C)>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+    G: FnMut(&T) -> bool,
+{
+    Search::new(start.clone(), successors).goal(is_goal).run()
+}
+
+pub fn astar<T, C, S, I, G, H>(
+    start: &T,
+    successors: S,
+    is_goal: G,
+    heuristic: H,
+) -> Option<(Vec<T>, C)>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+    G: FnMut(&T) -> bool,
+    H: FnMut(&T) -> C,
+{
+    Search::new(start.clone(), successors)
+        .goal(is_goal)
+        .heuristic(heuristic)
+        .run()
+}
+
+/// Cost from the nearest of `starts` to every reachable state
+pub fn dijkstra_distances<T, C, S, I, It>(starts: It, successors: S) -> HashMap<T, C>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+    It: IntoIterator<Item = T>,
+{
+    Search::from_starts(starts, successors).costs()
+}
+
+/// The cheapest path from any of `starts` to any goal, and its cost
+pub fn nearest_goal<T, C, S, I, G, It>(starts: It, successors: S, is_goal: G) -> Option<(Vec<T>, C)>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+    G: FnMut(&T) -> bool,
+    It: IntoIterator<Item = T>,
+{
+    Search::from_starts(starts, successors).goal(is_goal).run()
+}
+
+/// Cheapest cost between every (ordered) pair of `points` that are connected, with one search per
+/// point. Meant for compressing a big state space down to a handful of points of interest.
+pub fn all_pairs<T, C, S, I>(points: &[T], mut successors: S) -> HashMap<(T, T), C>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+{
+    let mut distances = HashMap::new();
+    for from in points {
+        let mut remaining: HashSet<&T> = points.iter().collect();
+        let costs = Search::new(from.clone(), &mut successors)
+            // stop as soon as every point has been found
+            .goal(|state| {
+                remaining.remove(state);
+                remaining.is_empty()
+            })
+            .costs();
+        for to in points {
+            if let Some(cost) = costs.get(to) {
+                distances.insert((from.clone(), to.clone()), cost.clone());
+            }
+        }
+    }
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Pos = (usize, usize);
+
+    fn grid_successors<const N: usize>(grid: &[[u64; N]], pos: Pos) -> Vec<(Pos, u64)> {
+        [
+            pos.0.checked_sub(1).map(|x| (x, pos.1)),
+            Some((pos.0 + 1, pos.1)),
+            pos.1.checked_sub(1).map(|y| (pos.0, y)),
+            Some((pos.0, pos.1 + 1)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|p| Some((p, *grid.get(p.1)?.get(p.0)?)))
+        .collect()
+    }
+
+    #[test]
+    fn test_dijkstra_u64_costs() {
+        // too big for the old i32 costs
+        let big = 1 << 40;
+        let grid = [[1, big, 1], [1, big, 1], [big, big, 1]];
+        let (path, cost) =
+            dijkstra(&(0, 0), |&p| grid_successors(&grid, p), |p| *p == (2, 2)).unwrap();
+        assert_eq!(cost, big + 3);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(2, 2)));
+    }
+
+    #[test]
+    fn test_astar_unreachable_goal() {
+        let grid = [[1, 1], [1, 1]];
+        let result = astar(
+            &(0, 0),
+            |&p| grid_successors(&grid, p),
+            |p| *p == (5, 5),
+            |_| 0,
+        );
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_inconsistent_heuristic() {
+        // the cheapest way to 'g' is s -> b -> a -> g (5), but b's estimate is high enough that
+        // a gets settled through the direct s -> a (4) first
+        let successors = |n: &char| match n {
+            's' => vec![('a', 4), ('b', 1)],
+            'b' => vec![('a', 1)],
+            'a' => vec![('g', 3)],
+            _ => vec![],
+        };
+        // never overestimates, but h(b) > w(b, a) + h(a)
+        let heuristic = |n: &char| if *n == 'b' { 4 } else { 0 };
+        let (path, cost) = astar(&'s', successors, |n| *n == 'g', heuristic).unwrap();
+        assert_eq!(path, vec!['s', 'b', 'a', 'g']);
+        assert_eq!(cost, 5);
+        let costs = Search::new('s', successors).heuristic(heuristic).costs();
+        assert_eq!(costs[&'a'], 2);
+        assert_eq!(costs[&'g'], 5);
+    }
+
+    #[test]
+    fn test_tuple_costs() {
+        // (turns, steps): fewest turns first, then fewest steps
+        let successors = |n: &u32| match n {
+            0 => vec![(1, (0, 5)), (2, (1, 1))],
+            1 | 2 => vec![(3, (0, 1))],
+            _ => vec![],
+        };
+        let (path, cost) = dijkstra(&0, successors, |n| *n == 3).unwrap();
+        assert_eq!(path, vec![0, 1, 3]);
+        assert_eq!(cost, (0_u32, 6_u32));
+    }
+
+    #[test]
+    fn test_multi_source() {
+        let line = |&n: &i64| [(n - 1, 1_u32), (n + 1, 1)];
+        let distances = Search::from_starts([0, 10], line).max_cost(2).costs();
+        assert_eq!(distances.len(), 10);
+        assert_eq!(distances[&8], 2);
+        assert_eq!(distances[&1], 1);
+
+        let (path, cost) = nearest_goal([0, 10], line, |&n| n == 7 || n == -5).unwrap();
+        assert_eq!(path, vec![10, 9, 8, 7]);
+        assert_eq!(cost, 3);
+    }
+
+    #[test]
+    fn test_all_pairs() {
+        let grid = [[1, 1, 1], [1, 9, 1], [1, 1, 1]];
+        let points = [(0, 0), (2, 2), (1, 1)];
+        let distances = all_pairs(&points, |&p| grid_successors(&grid, p));
+        assert_eq!(distances.len(), 9);
+        assert_eq!(distances[&((0, 0), (2, 2))], 4);
+        assert_eq!(distances[&((0, 0), (1, 1))], 10);
+        assert_eq!(distances[&((1, 1), (0, 0))], 2);
+        assert_eq!(distances[&((1, 1), (1, 1))], 0);
+    }
+
+    #[test]
+    fn test_max_cost_and_visit_limit() {
+        let line = |&n: &i64| [(n - 1, 1), (n + 1, 1)];
+        assert!(Search::new(0, line)
+            .goal(|&n| n == 5)
+            .max_cost(4)
+            .run()
+            .is_none());
+        assert!(Search::new(0, line)
+            .goal(|&n| n == 5)
+            .max_cost(5)
+            .run()
+            .is_some());
+        // 0, then +-1 .. +-4 before we get to 5
+        assert!(Search::new(0, line)
+            .goal(|&n| n == 5)
+            .visit_limit(9)
+            .run()
+            .is_none());
+        assert_eq!(Search::new(0, line).max_cost(3).costs().len(), 7);
+    }
+}
@@ -0,0 +1,230 @@
+//! Dijkstra that keeps every optimal predecessor instead of just one, for when ties matter (e.g.
+//! "how many tiles are on any best path").
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+use priority_queue::PriorityQueue;
+
+use super::search::Cost;
+use super::traversal::{dfs, reachable};
+
+/// Every optimal path from a start to the cheapest goal(s), stored as a predecessor DAG.
+///
+/// Assumes edge weights are non-negative; zero weight cycles give infinitely many optimal paths,
+/// and the counts/enumerations here will be wrong.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<T, C> {
+    start: T,
+    cost: C,
+    goals: Vec<T>,
+    predecessors: HashMap<T, Vec<T>>,
+}
+
+impl<T, C> ShortestPaths<T, C>
+where
+    T: Eq + Hash + Clone,
+{
+    pub const fn cost(&self) -> &C {
+        &self.cost
+    }
+
+    /// Every goal state reachable at the optimal cost
+    pub fn goals(&self) -> &[T] {
+        &self.goals
+    }
+
+    /// States immediately before `state` on some optimal path
+    pub fn predecessors(&self, state: &T) -> &[T] {
+        self.predecessors.get(state).map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of distinct optimal paths, summed over all optimal goals
+    pub fn count_paths(&self) -> usize {
+        // both dfs hooks need to see the counts
+        let counts: RefCell<HashMap<T, usize>> = RefCell::new(HashMap::new());
+        for goal in &self.goals {
+            // post-order over predecessors means every predecessor is counted before its successor
+            dfs(
+                goal,
+                |state| self.predecessors(state).to_vec(),
+                |state| !counts.borrow().contains_key(state),
+                |state| {
+                    let count = if *state == self.start {
+                        1
+                    } else {
+                        let counts = counts.borrow();
+                        self.predecessors(state)
+                            .iter()
+                            .map(|pred| counts.get(pred).copied().unwrap_or(0))
+                            .sum()
+                    };
+                    counts.borrow_mut().insert(state.clone(), count);
+                },
+            );
+        }
+        let counts = counts.into_inner();
+        self.goals.iter().map(|goal| counts[goal]).sum()
+    }
+
+    /// Every state that's on at least one optimal path (including start and goals)
+    pub fn states_on_paths(&self) -> HashSet<T> {
+        let mut states = HashSet::new();
+        for goal in &self.goals {
+            if !states.contains(goal) {
+                states.extend(reachable(goal, |state| self.predecessors(state).to_vec()));
+            }
+        }
+        states
+    }
+
+    /// Every optimal path, start to goal. There can be exponentially many of these, see
+    /// [`Self::count_paths`] first.
+    pub fn paths(&self) -> Vec<Vec<T>> {
+        let mut paths = vec![];
+        // partial paths, built backwards from the goal
+        let mut stack: Vec<Vec<T>> = self.goals.iter().map(|goal| vec![goal.clone()]).collect();
+        while let Some(partial) = stack.pop() {
+            let Some(last) = partial.last() else {
+                continue;
+            };
+            if *last == self.start {
+                paths.push(partial.into_iter().rev().collect());
+                continue;
+            }
+            for pred in self.predecessors(last) {
+                let mut next = partial.clone();
+                next.push(pred.clone());
+                stack.push(next);
+            }
+        }
+        paths
+    }
+}
+
+/// Like [`super::dijkstra`], but keeps every way of reaching each state at its optimal cost.
+/// Returns `None` if no goal is reachable.
+pub fn dijkstra_all<T, C, S, I, G>(
+    start: &T,
+    mut successors: S,
+    mut is_goal: G,
+) -> Option<ShortestPaths<T, C>>
+where
+    T: Eq + Hash + Clone,
+    C: Cost,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = (T, C)>,
+    G: FnMut(&T) -> bool,
+{
+    // PriorityQueue pops the max, so wrap in Reverse to get the cheapest
+    let mut to_visit = PriorityQueue::new();
+    let mut g_score: HashMap<T, C> = HashMap::new();
+    let mut predecessors: HashMap<T, Vec<T>> = HashMap::new();
+    let mut goals = vec![];
+    let mut best: Option<C> = None;
+    to_visit.push(start.clone(), Reverse(C::zero()));
+    g_score.insert(start.clone(), C::zero());
+
+    while let Some((current, Reverse(current_cost))) = to_visit.pop() {
+        if best.as_ref().is_some_and(|best| &current_cost > best) {
+            // everything left is worse than the goals we already have
+            break;
+        }
+        if is_goal(&current) {
+            best = Some(current_cost);
+            goals.push(current);
+            // keep going to pick up any other goals with the same cost, but a goal's successors
+            // can't be on a path to a different goal at the same cost (with positive weights)
+            continue;
+        }
+        for (neighbor, weight) in successors(&current) {
+            if neighbor == current {
+                continue;
+            }
+            let tentative_gscore = current_cost.add(&weight);
+            match g_score.get(&neighbor) {
+                Some(old_score) if &tentative_gscore > old_score => {}
+                Some(old_score) if &tentative_gscore == old_score => {
+                    predecessors
+                        .entry(neighbor)
+                        .or_default()
+                        .push(current.clone());
+                }
+                _ => {
+                    predecessors.insert(neighbor.clone(), vec![current.clone()]);
+                    to_visit.push(neighbor.clone(), Reverse(tentative_gscore.clone()));
+                    g_score.insert(neighbor, tentative_gscore);
+                }
+            }
+        }
+    }
+    Some(ShortestPaths {
+        start: start.clone(),
+        cost: best?,
+        goals,
+        predecessors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Pos = (usize, usize);
+
+    fn open_grid(size: usize) -> impl Fn(&Pos) -> Vec<(Pos, u32)> {
+        move |&(x, y)| {
+            [
+                (x + 1 < size).then_some((x + 1, y)),
+                (y + 1 < size).then_some((x, y + 1)),
+                x.checked_sub(1).map(|x| (x, y)),
+                y.checked_sub(1).map(|y| (x, y)),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|p| (p, 1))
+            .collect()
+        }
+    }
+
+    #[test]
+    fn test_count_grid_paths() {
+        let paths = dijkstra_all(&(0, 0), open_grid(4), |&p| p == (3, 3)).unwrap();
+        assert_eq!(*paths.cost(), 6);
+        // C(6, 3)
+        assert_eq!(paths.count_paths(), 20);
+        assert_eq!(paths.paths().len(), 20);
+        assert!(paths
+            .paths()
+            .iter()
+            .all(|path| path.len() == 7 && path[0] == (0, 0) && path[6] == (3, 3)));
+        assert_eq!(paths.states_on_paths().len(), 16);
+    }
+
+    #[test]
+    fn test_multiple_goals_and_excluded_detour() {
+        // 0 -> 1 -> 3 (cost 2), 0 -> 2 -> 4 (cost 2), 0 -> 5 -> 6 -> 4 (cost 3)
+        let successors = |n: &u32| match n {
+            0 => vec![(1, 1), (2, 1), (5, 1)],
+            1 => vec![(3, 1)],
+            2 | 6 => vec![(4, 1)],
+            5 => vec![(6, 1)],
+            _ => vec![],
+        };
+        let paths = dijkstra_all(&0, successors, |&n| n == 3 || n == 4).unwrap();
+        assert_eq!(*paths.cost(), 2_u32);
+        assert_eq!(paths.goals().len(), 2);
+        assert_eq!(paths.count_paths(), 2);
+        assert_eq!(
+            paths.states_on_paths(),
+            [0, 1, 2, 3, 4].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_unreachable() {
+        assert!(dijkstra_all(&(0, 0), open_grid(2), |&p| p == (5, 5)).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Unweighted traversals, using the same `successors` closure convention as the weighted
+//! searches but without the costs.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use hashbrown::{HashMap, HashSet};
+
+/// Number of steps from `start` to every state reachable from it
+pub fn bfs_distances<T, S, I>(start: &T, successors: S) -> HashMap<T, usize>
+where
+    T: Eq + Hash + Clone,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = T>,
+{
+    bfs_distances_from([start.clone()], successors)
+}
+
+/// Number of steps from the nearest of `starts` to every state reachable from any of them
+pub fn bfs_distances_from<T, S, I, It>(starts: It, mut successors: S) -> HashMap<T, usize>
+where
+    T: Eq + Hash + Clone,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = T>,
+    It: IntoIterator<Item = T>,
+{
+    let mut distances = HashMap::new();
+    let mut to_visit = VecDeque::new();
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            to_visit.push_back(start);
+        }
+    }
+    while let Some(current) = to_visit.pop_front() {
+        let distance = distances[&current] + 1;
+        for next in successors(&current) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance);
+                to_visit.push_back(next);
+            }
+        }
+    }
+    distances
+}
+
+/// The shortest path from `start` to a goal (inclusive of both) and its length in steps, or
+/// `None` if no goal is reachable
+pub fn bfs<T, S, I, G>(start: &T, mut successors: S, mut is_goal: G) -> Option<(Vec<T>, usize)>
+where
+    T: Eq + Hash + Clone,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = T>,
+    G: FnMut(&T) -> bool,
+{
+    // start has no parent, everything else points back towards it
+    let mut came_from: HashMap<T, Option<T>> = HashMap::new();
+    let mut to_visit = VecDeque::new();
+    came_from.insert(start.clone(), None);
+    to_visit.push_back(start.clone());
+    while let Some(current) = to_visit.pop_front() {
+        if is_goal(&current) {
+            let mut path = vec![current];
+            while let Some(Some(prev)) = came_from.get(path.last()?) {
+                path.push(prev.clone());
+            }
+            path.reverse();
+            let steps = path.len() - 1;
+            return Some((path, steps));
+        }
+        for next in successors(&current) {
+            if !came_from.contains_key(&next) {
+                came_from.insert(next.clone(), Some(current.clone()));
+                to_visit.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Every state reachable from `start`, including `start`
+pub fn reachable<T, S, I>(start: &T, mut successors: S) -> HashSet<T>
+where
+    T: Eq + Hash + Clone,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = T>,
+{
+    let mut seen = HashSet::new();
+    let mut to_visit = vec![start.clone()];
+    seen.insert(start.clone());
+    while let Some(current) = to_visit.pop() {
+        for next in successors(&current) {
+            if seen.insert(next.clone()) {
+                to_visit.push(next);
+            }
+        }
+    }
+    seen
+}
+
+/// Depth first traversal from `start`, visiting each state once.
+///
+/// `pre` is called when a state is first reached and returns whether to descend into it; `post`
+/// is called once everything below a state has been visited (so in post-order, and only for
+/// states that were descended into). Doesn't recurse, so it's fine on deep graphs.
+pub fn dfs<T, S, I, Pre, Post>(start: &T, mut successors: S, mut pre: Pre, mut post: Post)
+where
+    T: Eq + Hash + Clone,
+    S: FnMut(&T) -> I,
+    I: IntoIterator<Item = T>,
+    Pre: FnMut(&T) -> bool,
+    Post: FnMut(&T),
+{
+    let mut seen = HashSet::new();
+    seen.insert(start.clone());
+    if !pre(start) {
+        return;
+    }
+    let mut stack = vec![(start.clone(), successors(start).into_iter())];
+    while let Some((_, nexts)) = stack.last_mut() {
+        if let Some(next) = nexts.next() {
+            if seen.insert(next.clone()) && pre(&next) {
+                let next_nexts = successors(&next).into_iter();
+                stack.push((next, next_nexts));
+            }
+        } else if let Some((done, _)) = stack.pop() {
+            post(&done);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 1 -> 2 -> 3, 0 -> 4 -> 3, 5 unreachable
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn edges(n: &u32) -> Vec<u32> {
+        match n {
+            0 => vec![1, 4],
+            1 => vec![2],
+            2 | 4 => vec![3],
+            5 => vec![0],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let distances = bfs_distances(&0, edges);
+        assert_eq!(distances.len(), 5);
+        assert_eq!(distances[&3], 2);
+        assert!(!distances.contains_key(&5));
+    }
+
+    #[test]
+    fn test_bfs_distances_from() {
+        let distances = bfs_distances_from([5, 2], edges);
+        assert_eq!(distances[&3], 1);
+        assert_eq!(distances[&1], 2);
+        assert_eq!(distances.len(), 6);
+    }
+
+    #[test]
+    fn test_bfs_path() {
+        assert_eq!(bfs(&0, edges, |&n| n == 3), Some((vec![0, 4, 3], 2)));
+        assert_eq!(bfs(&0, edges, |&n| n == 0), Some((vec![0], 0)));
+        assert_eq!(bfs(&0, edges, |&n| n == 5), None);
+    }
+
+    #[test]
+    fn test_reachable() {
+        assert_eq!(reachable(&2, edges), [2, 3].into_iter().collect());
+    }
+
+    #[test]
+    fn test_dfs_order() {
+        let mut pre_order = vec![];
+        let mut post_order = vec![];
+        dfs(
+            &0,
+            edges,
+            |&n| {
+                pre_order.push(n);
+                n != 4
+            },
+            |&n| post_order.push(n),
+        );
+        assert_eq!(pre_order, vec![0, 1, 2, 3, 4]);
+        assert_eq!(post_order, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_dfs_deep() {
+        let mut count = 0;
+        dfs(
+            &0_u32,
+            |&n| (n < 1_000_000).then_some(n + 1),
+            |_| true,
+            |_| count += 1,
+        );
+        assert_eq!(count, 1_000_001);
+    }
+}
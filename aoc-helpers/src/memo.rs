@@ -0,0 +1,199 @@
+//! Runtime support for `#[aoc_macros::memoize]`.
+//!
+//! A [`MemoCache`] only lives as long as the outermost call into the memoized function, so
+//! nothing leaks from one input (or one test) into the next.
+
+use std::cell::{Cell, RefCell};
+use std::hash::{BuildHasher, Hash};
+
+use hashbrown::HashMap;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl MemoStats {
+    #[must_use]
+    pub const fn calls(&self) -> usize {
+        self.hits + self.misses
+    }
+}
+
+pub struct MemoCache<K, V> {
+    map: RefCell<HashMap<K, V>>,
+    depth: Cell<usize>,
+    stats: Cell<MemoStats>,
+    last_stats: Cell<MemoStats>,
+}
+
+impl<K, V> Default for MemoCache<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tracks how deep we are in the current call tree, and throws the cache away once the outermost
+/// call returns (or unwinds)
+struct DepthGuard<'a, K, V> {
+    cache: &'a MemoCache<K, V>,
+}
+
+impl<K, V> Drop for DepthGuard<'_, K, V> {
+    fn drop(&mut self) {
+        let depth = self.cache.depth.get() - 1;
+        self.cache.depth.set(depth);
+        if depth == 0 {
+            self.cache.map.borrow_mut().clear();
+            self.cache.last_stats.set(self.cache.stats.take());
+        }
+    }
+}
+
+impl<K, V> MemoCache<K, V> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            map: RefCell::new(HashMap::new()),
+            depth: Cell::new(0),
+            stats: Cell::new(MemoStats::default()),
+            last_stats: Cell::new(MemoStats::default()),
+        }
+    }
+
+    /// Stats for the call tree currently running, or the last one to finish if none is
+    pub const fn stats(&self) -> MemoStats {
+        if self.depth.get() == 0 {
+            self.last_stats.get()
+        } else {
+            self.stats.get()
+        }
+    }
+
+    fn enter(&self) -> DepthGuard<'_, K, V> {
+        self.depth.set(self.depth.get() + 1);
+        DepthGuard { cache: self }
+    }
+
+    fn record(&self, hit: bool) {
+        let mut stats = self.stats.get();
+        if hit {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+        self.stats.set(stats);
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> MemoCache<K, V> {
+    /// `compute` is free to recurse back into this cache, no borrow is held while it runs
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&self, key: K, compute: F) -> V {
+        let _guard = self.enter();
+        let hash = self.map.borrow().hasher().hash_one(&key);
+        if let Some(value) = self.lookup(hash, |k| *k == key) {
+            return value;
+        }
+        self.insert(hash, key, compute())
+    }
+
+    /// Like [`Self::get_or_insert_with`], but looks up a borrowed form of the key so the owned key
+    /// is only built (by `to_owned`) on a miss. `key` has to hash the same as the `K` it stands
+    /// for, which holds for tuples of [`Borrow`](std::borrow::Borrow)ed parts, and `matches`
+    /// decides whether a stored key is equal to it.
+    pub fn get_or_insert_with_borrowed<Q, M, O, F>(
+        &self,
+        key: &Q,
+        matches: M,
+        to_owned: O,
+        compute: F,
+    ) -> V
+    where
+        Q: Hash + ?Sized,
+        M: FnMut(&K) -> bool,
+        O: FnOnce() -> K,
+        F: FnOnce() -> V,
+    {
+        let _guard = self.enter();
+        let hash = self.map.borrow().hasher().hash_one(key);
+        if let Some(value) = self.lookup(hash, matches) {
+            return value;
+        }
+        self.insert(hash, to_owned(), compute())
+    }
+
+    fn lookup(&self, hash: u64, matches: impl FnMut(&K) -> bool) -> Option<V> {
+        let value = self
+            .map
+            .borrow()
+            .raw_entry()
+            .from_hash(hash, matches)
+            .map(|(_, value)| value.clone());
+        self.record(value.is_some());
+        value
+    }
+
+    fn insert(&self, hash: u64, key: K, value: V) -> V {
+        self.map
+            .borrow_mut()
+            .raw_entry_mut()
+            .from_key_hashed_nocheck(hash, &key)
+            .insert(key, value.clone());
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    thread_local! {
+        static FIB: MemoCache<u64, u64> = MemoCache::new();
+    }
+
+    fn fib(n: u64) -> u64 {
+        FIB.with(|cache| {
+            cache.get_or_insert_with(n, || if n < 2 { n } else { fib(n - 1) + fib(n - 2) })
+        })
+    }
+
+    #[test]
+    fn test_recursion_hits_cache() {
+        assert_eq!(fib(50), 12_586_269_025);
+        let stats = FIB.with(MemoCache::stats);
+        assert_eq!(stats.misses, 51);
+        assert_eq!(stats.hits, 48);
+    }
+
+    #[test]
+    fn test_cache_is_per_call_tree() {
+        fib(10);
+        fib(10);
+        // second call starts from an empty cache again
+        assert_eq!(FIB.with(MemoCache::stats).misses, 11);
+    }
+
+    #[test]
+    fn test_borrowed_lookup_only_builds_key_on_miss() {
+        let cache: MemoCache<Vec<u8>, usize> = MemoCache::new();
+        let owned_keys = Cell::new(0);
+        let lookup = |key: &[u8]| {
+            cache.get_or_insert_with_borrowed(
+                &key,
+                |stored| stored.as_slice() == key,
+                || {
+                    owned_keys.set(owned_keys.get() + 1);
+                    key.to_vec()
+                },
+                || key.len(),
+            )
+        };
+        let _guard = cache.enter();
+        assert_eq!(lookup(b"abc"), 3);
+        assert_eq!(lookup(b"abc"), 3);
+        assert_eq!(lookup(b"de"), 2);
+        assert_eq!(owned_keys.get(), 2);
+        assert_eq!(cache.stats(), MemoStats { hits: 1, misses: 2 });
+    }
+}
@@ -0,0 +1,357 @@
+//! Sets of integers stored as ranges, and maps that shift pieces of the number line around.
+//!
+//! Everything is half open (`start..end`), `RangeInclusive`s are converted on the way in.
+
+use std::ops::{Range, RangeInclusive};
+
+/// A set of `i64`s, kept as sorted, non-overlapping, non-touching, non-empty ranges
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    ranges: Vec<Range<i64>>,
+}
+
+impl RangeSet {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { ranges: vec![] }
+    }
+
+    /// The normalized ranges, in order
+    #[must_use]
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// How many numbers are in the set
+    #[must_use]
+    pub fn total_len(&self) -> i64 {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+
+    #[must_use]
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    #[must_use]
+    pub fn max(&self) -> Option<i64> {
+        self.ranges.last().map(|r| r.end - 1)
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: i64) -> bool {
+        // first range that ends after value is the only one that could contain it
+        let idx = self.ranges.partition_point(|r| r.end <= value);
+        self.ranges.get(idx).is_some_and(|r| r.start <= value)
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        // everything from first to last (exclusive) overlaps or touches the new range
+        let first = self.ranges.partition_point(|r| r.end < range.start);
+        let last = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if first < last {
+            range.start.min(self.ranges[first].start)..range.end.max(self.ranges[last - 1].end)
+        } else {
+            range
+        };
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let first = self.ranges.partition_point(|r| r.end <= range.start);
+        let last = self.ranges.partition_point(|r| r.start < range.end);
+        if first >= last {
+            return;
+        }
+        let before = self.ranges[first].start..range.start;
+        let after = range.end..self.ranges[last - 1].end;
+        let leftovers = [before, after].into_iter().filter(|r| !r.is_empty());
+        self.ranges.splice(first..last, leftovers);
+    }
+
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in &other.ranges {
+            result.insert(range.clone());
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for range in &other.ranges {
+            result.remove(range.clone());
+        }
+        result
+    }
+
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let overlap = a.start.max(b.start)..a.end.min(b.end);
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        // can't touch, they came from ranges that didn't
+        Self { ranges }
+    }
+
+    /// Everything below `at`, and everything from `at` up
+    #[must_use]
+    pub fn split_at(&self, at: i64) -> (Self, Self) {
+        let mut below = self.clone();
+        below.remove(at..i64::MAX);
+        let mut above = self.clone();
+        above.remove(i64::MIN..at);
+        (below, above)
+    }
+}
+
+impl From<Range<i64>> for RangeSet {
+    fn from(range: Range<i64>) -> Self {
+        std::iter::once(range).collect()
+    }
+}
+
+impl From<RangeInclusive<i64>> for RangeSet {
+    fn from(range: RangeInclusive<i64>) -> Self {
+        std::iter::once(range).collect()
+    }
+}
+
+impl FromIterator<Range<i64>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl FromIterator<RangeInclusive<i64>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<i64>>>(iter: I) -> Self {
+        iter.into_iter()
+            .map(|range| *range.start()..*range.end() + 1)
+            .collect()
+    }
+}
+
+/// A function on `i64`s that adds a fixed offset within each of a set of disjoint ranges, and
+/// leaves everything else alone
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeMap {
+    /// Sorted, non-overlapping, no zero offsets
+    pieces: Vec<(Range<i64>, i64)>,
+}
+
+impl RangeMap {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { pieces: vec![] }
+    }
+
+    /// Map `source` to `source + offset`, replacing whatever was there before
+    pub fn insert(&mut self, source: Range<i64>, offset: i64) {
+        if source.is_empty() {
+            return;
+        }
+        let first = self.pieces.partition_point(|(r, _)| r.end <= source.start);
+        let last = self.pieces.partition_point(|(r, _)| r.start < source.end);
+        let mut replacement = vec![];
+        if first < last {
+            let (before, before_offset) = &self.pieces[first];
+            if before.start < source.start {
+                replacement.push((before.start..source.start, *before_offset));
+            }
+        }
+        if offset != 0 {
+            replacement.push((source.clone(), offset));
+        }
+        if first < last {
+            let (after, after_offset) = &self.pieces[last - 1];
+            if source.end < after.end {
+                replacement.push((source.end..after.end, *after_offset));
+            }
+        }
+        self.pieces.splice(first..last, replacement);
+    }
+
+    /// Day 5 style "`dest` `source` `len`" mapping
+    pub fn insert_mapping(&mut self, dest_start: i64, source_start: i64, len: i64) {
+        self.insert(source_start..source_start + len, dest_start - source_start);
+    }
+
+    #[must_use]
+    pub fn get(&self, value: i64) -> i64 {
+        let idx = self.pieces.partition_point(|(r, _)| r.end <= value);
+        match self.pieces.get(idx) {
+            Some((range, offset)) if range.start <= value => value + offset,
+            _ => value,
+        }
+    }
+
+    /// The whole number line cut into pieces, with the offset for each (including the gaps
+    /// between pieces with an offset of 0)
+    fn segments(&self) -> Vec<(Range<i64>, i64)> {
+        let mut segments = vec![];
+        let mut covered_to = i64::MIN;
+        for (range, offset) in &self.pieces {
+            if covered_to < range.start {
+                segments.push((covered_to..range.start, 0));
+            }
+            segments.push((range.clone(), *offset));
+            covered_to = range.end;
+        }
+        if covered_to < i64::MAX {
+            segments.push((covered_to..i64::MAX, 0));
+        }
+        segments
+    }
+
+    /// Where every number in `set` ends up
+    #[must_use]
+    pub fn apply(&self, set: &RangeSet) -> RangeSet {
+        let mut result = RangeSet::new();
+        for range in set.ranges() {
+            for (segment, offset) in self.segments() {
+                let overlap = range.start.max(segment.start)..range.end.min(segment.end);
+                if !overlap.is_empty() {
+                    result.insert(overlap.start + offset..overlap.end + offset);
+                }
+            }
+        }
+        result
+    }
+
+    /// A single map doing `self` and then `then`
+    #[must_use]
+    pub fn compose(&self, then: &Self) -> Self {
+        let then_segments = then.segments();
+        let mut pieces: Vec<(Range<i64>, i64)> = vec![];
+        for (segment, offset) in self.segments() {
+            let image = segment.start + offset..segment.end + offset;
+            for (then_segment, then_offset) in &then_segments {
+                let overlap = image.start.max(then_segment.start)..image.end.min(then_segment.end);
+                if overlap.is_empty() {
+                    continue;
+                }
+                let source = overlap.start - offset..overlap.end - offset;
+                let total_offset = offset + then_offset;
+                if total_offset == 0 {
+                    continue;
+                }
+                // extend the previous piece if this one carries straight on from it
+                match pieces.last_mut() {
+                    Some((last, last_offset))
+                        if (last.end, *last_offset) == (source.start, total_offset) =>
+                    {
+                        last.end = source.end;
+                    }
+                    _ => pieces.push((source, total_offset)),
+                }
+            }
+        }
+        Self { pieces }
+    }
+}
+
+impl FromIterator<(Range<i64>, i64)> for RangeMap {
+    fn from_iter<I: IntoIterator<Item = (Range<i64>, i64)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (source, offset) in iter {
+            map.insert(source, offset);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_set_normalizes() {
+        let set: RangeSet = [5..10, 0..3, 3..4, 8..12, 20..20].into_iter().collect();
+        assert_eq!(set.ranges(), [0..4, 5..12]);
+        assert_eq!(set.total_len(), 11);
+        assert!(set.contains(11));
+        assert!(!set.contains(4));
+        assert_eq!((set.min(), set.max()), (Some(0), Some(11)));
+        let inclusive: RangeSet = [0..=3, 5..=11].into_iter().collect();
+        assert_eq!(inclusive, set);
+    }
+
+    #[test]
+    fn test_range_set_algebra() {
+        let a: RangeSet = [0..10, 20..30].into_iter().collect();
+        let b = RangeSet::from(5..25);
+        assert_eq!(a.union(&b).ranges(), [0..30]);
+        assert_eq!(a.intersection(&b).ranges(), [5..10, 20..25]);
+        assert_eq!(a.difference(&b).ranges(), [0..5, 25..30]);
+        assert_eq!(b.difference(&a).ranges(), [10..20]);
+        let (below, above) = a.split_at(5);
+        assert_eq!(below.ranges(), [0..5]);
+        assert_eq!(above.ranges(), [5..10, 20..30]);
+    }
+
+    #[test]
+    fn test_range_map_day5_example() {
+        // seed-to-soil from the example
+        let mut map = RangeMap::new();
+        map.insert_mapping(50, 98, 2);
+        map.insert_mapping(52, 50, 48);
+        assert_eq!(map.get(79), 81);
+        assert_eq!(map.get(14), 14);
+        assert_eq!(map.get(99), 51);
+        let seeds: RangeSet = [79..93, 55..68].into_iter().collect();
+        assert_eq!(map.apply(&seeds).ranges(), [57..70, 81..95]);
+        assert_eq!(
+            map.apply(&RangeSet::from(90..100)).ranges(),
+            [50..52, 92..100]
+        );
+    }
+
+    #[test]
+    fn test_range_map_insert_overrides() {
+        let mut map = RangeMap::new();
+        map.insert(0..10, 5);
+        map.insert(3..5, -1);
+        map.insert(8..12, 0);
+        assert_eq!(map.pieces, [(0..3, 5), (3..5, -1), (5..8, 5)]);
+    }
+
+    #[test]
+    fn test_range_map_compose() {
+        let first: RangeMap = [(0..10, 100), (10..20, -10)].into_iter().collect();
+        let second: RangeMap = [(0..5, 1000), (105..110, -105)].into_iter().collect();
+        let composed = first.compose(&second);
+        for value in -5..30 {
+            assert_eq!(composed.get(value), second.get(first.get(value)), "{value}");
+        }
+        let set: RangeSet = (-5..30).into();
+        assert_eq!(composed.apply(&set), second.apply(&first.apply(&set)));
+    }
+}
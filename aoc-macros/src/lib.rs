@@ -1,25 +1,48 @@
 use proc_macro2::Ident;
-use quote::{quote, quote_spanned};
+use quote::quote;
+use syn::spanned::Spanned;
 
-fn elems_field(ast: &syn::DeriveInput) -> Option<&syn::Field> {
-    if let syn::Data::Struct(syn::DataStruct {
-        fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
-        ..
-    }) = &ast.data
-    {
-        named.iter().find(|elem| {
-            if let Some(ident) = &elem.ident {
-                ident == "elems"
+mod memoize;
+
+fn elems_field(ast: &syn::DeriveInput) -> syn::Result<&syn::Field> {
+    let fields = match &ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(syn::FieldsNamed { named, .. }),
+            ..
+        }) => named,
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => {
+            let message = "TwoDArray can only be derived for structs with named fields";
+            return Err(if fields.is_empty() {
+                syn::Error::new(ast.ident.span(), message)
             } else {
-                false
-            }
+                syn::Error::new_spanned(fields, message)
+            });
+        }
+        syn::Data::Enum(syn::DataEnum { enum_token, .. }) => {
+            return Err(syn::Error::new(
+                enum_token.span(),
+                "TwoDArray can only be derived for structs, not enums",
+            ));
+        }
+        syn::Data::Union(syn::DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new(
+                union_token.span(),
+                "TwoDArray can only be derived for structs, not unions",
+            ));
+        }
+    };
+    fields
+        .iter()
+        .find(|elem| elem.ident.as_ref().is_some_and(|ident| ident == "elems"))
+        .ok_or_else(|| {
+            syn::Error::new(
+                ast.ident.span(),
+                "TwoDArray requires a field named `elems` of type `Vec<Vec<T>>`",
+            )
         })
-    } else {
-        None
-    }
 }
 
-fn generate_indexing(struct_ident: Ident, elems_type: Ident) -> proc_macro2::TokenStream {
+fn generate_indexing(struct_ident: &Ident, elems_type: &syn::Type) -> proc_macro2::TokenStream {
     quote! {
         impl std::ops::Index<(usize, usize)> for #struct_ident {
             type Output = #elems_type;
@@ -48,45 +71,33 @@ fn generate_indexing(struct_ident: Ident, elems_type: Ident) -> proc_macro2::Tok
     }
 }
 
-fn find_contained_elem_type(ty: &syn::Type) -> Option<syn::Ident> {
+/// The `T` in `Vec<T>`, if `ty` is a `Vec` at all
+fn vec_elem_type(ty: &syn::Type) -> Option<&syn::Type> {
     let syn::Type::Path(syn::TypePath { path, .. }) = ty else {
         return None;
     };
-    let syn::Path { segments, .. } = path;
-    if !segments[0].ident.to_string().ends_with("Vec") {
+    let segment = path.segments.last()?;
+    if segment.ident != "Vec" {
         return None;
     }
-    let syn::PathSegment {
-        arguments:
-            syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }),
-        ..
-    } = &segments[0]
+    let syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }) =
+        &segment.arguments
     else {
         return None;
     };
-    let syn::GenericArgument::Type(syn::Type::Path(syn::TypePath { path, .. })) = &args[0] else {
-        return None;
-    };
-    let syn::Path { segments, .. } = path;
-    if !segments[0].ident.to_string().ends_with("Vec") {
-        return None;
+    match args.first()? {
+        syn::GenericArgument::Type(ty) => Some(ty),
+        _ => None,
     }
-    let syn::PathSegment {
-        arguments:
-            syn::PathArguments::AngleBracketed(syn::AngleBracketedGenericArguments { args, .. }),
-        ..
-    } = &segments[0]
-    else {
-        return None;
-    };
-    let syn::GenericArgument::Type(syn::Type::Path(syn::TypePath { path, .. })) = &args[0] else {
-        return None;
-    };
-    let syn::Path { segments, .. } = path;
-    Some(segments[0].ident.clone())
 }
 
-fn impl_useful_functs(struct_ident: Ident, _elems_type: Ident) -> proc_macro2::TokenStream {
+fn find_contained_elem_type(ty: &syn::Type) -> syn::Result<&syn::Type> {
+    vec_elem_type(ty)
+        .and_then(vec_elem_type)
+        .ok_or_else(|| syn::Error::new_spanned(ty, "`elems` must be of type `Vec<Vec<T>>`"))
+}
+
+fn impl_useful_functs(struct_ident: &Ident) -> proc_macro2::TokenStream {
     quote! {
         impl #struct_ident {
             pub fn y_len(&self) -> usize {
@@ -103,20 +114,47 @@ fn impl_useful_functs(struct_ident: Ident, _elems_type: Ident) -> proc_macro2::T
     }
 }
 
-#[proc_macro_derive(TwoDArray)]
-pub fn make_2d_array(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
-    let Some(elems_field) = elems_field(&ast) else {
-        return quote_spanned!(ast.ident.span() => compile_error!("Must contain elems field");)
-            .into();
-    };
-    let elems_type = &elems_field.ty;
-    let contained_type = find_contained_elem_type(elems_type).unwrap();
-    let index_impls = generate_indexing(ast.ident.clone(), contained_type.clone());
-    let useful_functions = impl_useful_functs(ast.ident.clone(), contained_type);
-    quote! {
+fn derive_2d_array(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !ast.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &ast.generics,
+            "TwoDArray cannot be derived for generic structs",
+        ));
+    }
+    let elems_field = elems_field(ast)?;
+    let contained_type = find_contained_elem_type(&elems_field.ty)?;
+    let index_impls = generate_indexing(&ast.ident, contained_type);
+    let useful_functions = impl_useful_functs(&ast.ident);
+    Ok(quote! {
         #index_impls
         #useful_functions
-    }
-    .into()
+    })
+}
+
+#[proc_macro_derive(TwoDArray)]
+pub fn make_2d_array(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    derive_2d_array(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Caches a function's results for the duration of its outermost call, so recursive calls share
+/// the cache but separate top level calls don't. Reference arguments (e.g. `&[T]`, `&str`) are
+/// keyed on their contents, everything else is cloned into the key. Lookups hash the arguments as
+/// they are, the owned key is only built on a cache miss.
+///
+/// Also generates `<name>_memo_stats()`, reporting cache hits/misses for the latest call.
+///
+/// The expansion refers to `::aoc_helpers::memo` directly, so the crate using this attribute has
+/// to depend on `aoc_helpers`.
+#[proc_macro_attribute]
+pub fn memoize(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let item = syn::parse_macro_input!(item as syn::ItemFn);
+    memoize::memoize(attr.into(), item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
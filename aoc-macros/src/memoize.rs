@@ -0,0 +1,146 @@
+use quote::{format_ident, quote};
+
+/// How a single argument takes part in the cache key
+struct KeyPart {
+    /// Its type in the stored key
+    ty: proc_macro2::TokenStream,
+    /// A reference that hashes the same as the stored key part, used for lookups
+    borrowed: proc_macro2::TokenStream,
+    /// Builds the stored key part, only run on a cache miss
+    owned: proc_macro2::TokenStream,
+    /// Compares the stored key part (`stored`) with the argument
+    matches: Box<dyn Fn(&proc_macro2::TokenStream) -> proc_macro2::TokenStream>,
+}
+
+fn key_part(arg: &syn::FnArg) -> syn::Result<KeyPart> {
+    let syn::FnArg::Typed(syn::PatType { pat, ty, .. }) = arg else {
+        return Err(syn::Error::new_spanned(
+            arg,
+            "memoize cannot be used on methods, the receiver can't be part of the cache key",
+        ));
+    };
+    let syn::Pat::Ident(syn::PatIdent {
+        ident,
+        by_ref: None,
+        subpat: None,
+        ..
+    }) = &**pat
+    else {
+        return Err(syn::Error::new_spanned(
+            pat,
+            "memoize requires plain identifiers as argument patterns",
+        ));
+    };
+    match &**ty {
+        syn::Type::Reference(syn::TypeReference {
+            mutability: Some(mutability),
+            ..
+        }) => Err(syn::Error::new_spanned(
+            mutability,
+            "memoize cannot cache functions that take mutable references",
+        )),
+        // Borrowed args (including slices and strs) are keyed on their contents, `Borrow` promises
+        // the owned form hashes the same so lookups never have to copy them
+        syn::Type::Reference(syn::TypeReference { elem, .. }) => {
+            let owned_ty = quote!(<#elem as ::std::borrow::ToOwned>::Owned);
+            let (elem, ident) = (elem.clone(), ident.clone());
+            Ok(KeyPart {
+                ty: owned_ty.clone(),
+                borrowed: quote!(#ident),
+                owned: quote!(::std::borrow::ToOwned::to_owned(#ident)),
+                matches: Box::new(move |stored| {
+                    quote!(<#owned_ty as ::std::borrow::Borrow<#elem>>::borrow(#stored) == #ident)
+                }),
+            })
+        }
+        syn::Type::ImplTrait(_) => Err(syn::Error::new_spanned(
+            ty,
+            "memoize cannot cache `impl Trait` arguments",
+        )),
+        _ => {
+            let ident = ident.clone();
+            Ok(KeyPart {
+                ty: quote!(#ty),
+                borrowed: quote!(&#ident),
+                owned: quote!(::std::clone::Clone::clone(&#ident)),
+                matches: Box::new(move |stored| quote!(*#stored == #ident)),
+            })
+        }
+    }
+}
+
+pub fn memoize(
+    attr: proc_macro2::TokenStream,
+    item: syn::ItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if !attr.is_empty() {
+        return Err(syn::Error::new_spanned(attr, "memoize takes no arguments"));
+    }
+    let syn::ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "memoize cannot be used on async functions",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "memoize cannot be used on generic functions, the cache has to have a concrete type",
+        ));
+    }
+    let syn::ReturnType::Type(_, return_type) = &sig.output else {
+        return Err(syn::Error::new_spanned(
+            &sig,
+            "memoize requires the function to return a value",
+        ));
+    };
+    let parts = sig
+        .inputs
+        .iter()
+        .map(key_part)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let key_types = parts.iter().map(|part| &part.ty);
+    let borrowed = parts.iter().map(|part| &part.borrowed);
+    let owned = parts.iter().map(|part| &part.owned);
+    let matches = parts.iter().enumerate().map(|(idx, part)| {
+        let idx = syn::Index::from(idx);
+        (part.matches)(&quote!(&__memo_key.#idx))
+    });
+
+    let cache_ident = format_ident!("{}_MEMO_CACHE", sig.ident.to_string().to_uppercase());
+    let stats_ident = format_ident!("{}_memo_stats", sig.ident);
+    let stats_doc = format!(
+        "Cache hits and misses for the most recent call to `{}`",
+        sig.ident
+    );
+    Ok(quote! {
+        thread_local! {
+            static #cache_ident: ::aoc_helpers::memo::MemoCache<(#(#key_types,)*), #return_type> =
+                ::aoc_helpers::memo::MemoCache::new();
+        }
+
+        #[doc = #stats_doc]
+        #[allow(dead_code)]
+        #vis fn #stats_ident() -> ::aoc_helpers::memo::MemoStats {
+            #cache_ident.with(::aoc_helpers::memo::MemoCache::stats)
+        }
+
+        #(#attrs)*
+        #vis #sig {
+            #cache_ident.with(|cache| {
+                cache.get_or_insert_with_borrowed(
+                    &(#(#borrowed,)*),
+                    |__memo_key| #(#matches &&)* true,
+                    || (#(#owned,)*),
+                    || #block,
+                )
+            })
+        }
+    })
+}
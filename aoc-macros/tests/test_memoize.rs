@@ -0,0 +1,41 @@
+#[aoc_macros::memoize]
+fn count_paths(grid: &[Vec<bool>], x: usize, y: usize) -> u64 {
+    if !grid[y][x] {
+        return 0;
+    }
+    if x == 0 && y == 0 {
+        return 1;
+    }
+    let from_left = if x > 0 { count_paths(grid, x - 1, y) } else { 0 };
+    let from_above = if y > 0 { count_paths(grid, x, y - 1) } else { 0 };
+    from_left + from_above
+}
+
+#[aoc_macros::memoize]
+fn prefix_splits(word: &str) -> usize {
+    (1..word.len()).map(|n| 1 + prefix_splits(&word[n..])).sum()
+}
+
+#[test]
+fn test_memoize_slices() {
+    let grid = vec![vec![true; 16]; 16];
+    // C(30, 15)
+    assert_eq!(count_paths(&grid, 15, 15), 155_117_520);
+    let stats = count_paths_memo_stats();
+    assert_eq!(stats.misses, 16 * 16);
+    assert!(stats.hits > 0);
+}
+
+#[test]
+fn test_memoize_does_not_leak_between_calls() {
+    let mut grid = vec![vec![true; 3]; 3];
+    assert_eq!(count_paths(&grid, 2, 2), 6);
+    grid[1][1] = false;
+    assert_eq!(count_paths(&grid, 2, 2), 2);
+}
+
+#[test]
+fn test_memoize_str() {
+    assert_eq!(prefix_splits("abcd"), 7);
+    assert_eq!(prefix_splits_memo_stats().misses, 4);
+}
@@ -0,0 +1,6 @@
+#[derive(aoc_macros::TwoDArray)]
+struct Board {
+    elems: Vec<char>,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(aoc_macros::TwoDArray)]
+enum Board {
+    Empty,
+    Full { elems: Vec<Vec<char>> },
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[derive(aoc_macros::TwoDArray)]
+struct Board<T> {
+    elems: Vec<Vec<T>>,
+}
+
+fn main() {}
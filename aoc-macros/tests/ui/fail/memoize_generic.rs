@@ -0,0 +1,6 @@
+#[aoc_macros::memoize]
+fn count<T: Clone>(items: &[T]) -> usize {
+    items.len()
+}
+
+fn main() {}
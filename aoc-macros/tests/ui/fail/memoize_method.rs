@@ -0,0 +1,10 @@
+struct Counter;
+
+impl Counter {
+    #[aoc_macros::memoize]
+    fn count(&self, n: u64) -> u64 {
+        n
+    }
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[aoc_macros::memoize]
+fn count(items: &mut Vec<u64>) -> usize {
+    items.len()
+}
+
+fn main() {}
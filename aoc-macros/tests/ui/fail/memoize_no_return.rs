@@ -0,0 +1,6 @@
+#[aoc_macros::memoize]
+fn count(n: u64) {
+    println!("{n}");
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[aoc_macros::memoize]
+fn count((x, y): (u64, u64)) -> u64 {
+    x + y
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[aoc_macros::memoize(size = 10)]
+fn count(n: u64) -> u64 {
+    n
+}
+
+fn main() {}
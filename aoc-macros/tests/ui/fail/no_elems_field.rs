@@ -0,0 +1,6 @@
+#[derive(aoc_macros::TwoDArray)]
+struct Board {
+    tiles: Vec<Vec<char>>,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+#[derive(aoc_macros::TwoDArray)]
+union Board {
+    elems: std::mem::ManuallyDrop<Vec<Vec<char>>>,
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[derive(aoc_macros::TwoDArray)]
+struct Board;
+
+fn main() {}
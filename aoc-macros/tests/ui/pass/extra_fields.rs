@@ -0,0 +1,18 @@
+#[derive(aoc_macros::TwoDArray)]
+struct Board {
+    light: Vec<(usize, usize)>,
+    elems: Vec<Vec<char>>,
+    seen: usize,
+}
+
+fn main() {
+    let board = Board {
+        light: vec![],
+        elems: vec![vec!['.'; 3]; 2],
+        seen: 0,
+    };
+    assert_eq!(board[(2, 1)], '.');
+    assert_eq!(board.x_len(), 3);
+    assert_eq!(board.y_len(), 2);
+    assert_eq!(board.light.len() + board.seen, 0);
+}
@@ -0,0 +1,12 @@
+#[derive(aoc_macros::TwoDArray)]
+struct Board {
+    elems: std::vec::Vec<Vec<(usize, Option<char>)>>,
+}
+
+fn main() {
+    let mut board = Board {
+        elems: vec![vec![(0, None); 2]; 2],
+    };
+    board[(1, 0)] = (1, Some('#'));
+    assert_eq!(board[0][1], (1, Some('#')));
+}